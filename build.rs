@@ -12,6 +12,7 @@ use zip::{result::ZipError, write::FileOptions, CompressionMethod};
 
 fn main() {
     let profile = env::var("PROFILE").unwrap();
+    println!("cargo:rustc-check-cfg=cfg(build, values(\"debug\", \"release\"))");
     println!("cargo:rustc-cfg=build={:?}", &profile);
 
     if profile == "debug" {
@@ -34,7 +35,7 @@ fn main() {
     }
 
     if cfg!(target_os = "windows") {
-        let _ = WindowsResource::new()
+        WindowsResource::new()
             .set_icon("resources/icon.ico")
             .compile()
             .unwrap();
@@ -60,18 +61,23 @@ where
     for entry in it {
         let path = entry.path();
         let name = path.strip_prefix(Path::new(prefix)).unwrap();
+        let zip_name = name
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
 
         if path.is_file() {
             println!("adding file {:?} as {:?} ...", path, name);
-            zip.start_file_from_path(name, options)?;
+            zip.start_file(zip_name, options)?;
             let mut f = File::open(path)?;
 
             f.read_to_end(&mut buffer)?;
-            zip.write_all(&*buffer)?;
+            zip.write_all(&buffer)?;
             buffer.clear();
-        } else if name.as_os_str().len() != 0 {
+        } else if !name.as_os_str().is_empty() {
             println!("adding dir {:?} as {:?} ...", path, name);
-            zip.add_directory_from_path(name, options)?;
+            zip.add_directory(zip_name, options)?;
         }
     }
 
@@ -89,9 +95,9 @@ fn compress(
     }
 
     let path = Path::new(dst_file);
-    let file = File::create(&path).unwrap();
+    let file = File::create(path).unwrap();
 
-    let walkdir = WalkDir::new(src_dir.to_string());
+    let walkdir = WalkDir::new(src_dir);
     let it = walkdir.into_iter();
 
     zip_dir(&mut it.filter_map(|e| e.ok()), src_dir, file, method)?;
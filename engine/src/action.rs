@@ -0,0 +1,23 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq)]
+pub enum Action {
+    MoveRight,
+    MoveLeft,
+    MoveDown,
+    RotateClockwise,
+    RotateCounterClockwise,
+    Rotate180,
+    HardDrop,
+    /// Drops the piece onto the stack without locking it, it was the soft drop of the older versions
+    SonicDrop,
+    /// The piece falls faster until the soft drop is stopped
+    SoftDropStart,
+    SoftDropStop,
+    HoldPiece,
+
+    /// Lines of garbage sent by the opponent
+    Garbage(u32),
+
+    GameOver,
+}
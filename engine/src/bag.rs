@@ -0,0 +1,93 @@
+use std::{
+    collections::{vec_deque::Iter, VecDeque},
+    iter::Take,
+};
+
+use rand::{rngs::StdRng, SeedableRng};
+
+use crate::{
+    randomizer::{Randomizer, RandomizerType},
+    shape::ShapeType,
+};
+
+/// Number of the upcoming pieces that are known in advance
+const QUEUE_LENGTH: usize = 14;
+
+/// Queue of the upcoming pieces
+#[derive(Clone)]
+pub struct Bag {
+    bag: VecDeque<ShapeType>,
+    randomizer: Box<dyn Randomizer>,
+    rng: StdRng,
+}
+
+impl Bag {
+    pub fn new(seed: &[u8; 32], randomizer: RandomizerType) -> Bag {
+        let rng: StdRng = SeedableRng::from_seed(*seed);
+
+        let mut bag = Bag {
+            bag: VecDeque::with_capacity(QUEUE_LENGTH),
+            randomizer: randomizer.create(),
+            rng,
+        };
+
+        bag.fill();
+        bag
+    }
+
+    pub fn pop(&mut self) -> ShapeType {
+        let shape = self.bag.pop_front();
+        self.fill();
+        shape.unwrap()
+    }
+
+    pub fn peek(&self, n: usize) -> Take<Iter<'_, ShapeType>> {
+        self.bag.iter().take(n)
+    }
+
+    fn fill(&mut self) {
+        while self.bag.len() < QUEUE_LENGTH {
+            let shape = self.randomizer.next(&mut self.rng);
+            self.bag.push_back(shape);
+        }
+    }
+}
+
+#[test]
+fn bag_test() {
+    let seed = [0; 32];
+    let mut bag = Bag::new(&seed, RandomizerType::Bag7);
+    assert_eq!(14, bag.peek(14).len());
+
+    for _ in 0..7 {
+        bag.pop();
+    }
+
+    let mut types = Vec::<ShapeType>::with_capacity(7);
+
+    for _ in 0..7 {
+        let shape = bag.pop();
+        types.push(shape);
+    }
+
+    let shapes = crate::shape::all_shape_types();
+
+    for shape in shapes {
+        assert!(types.contains(&shape));
+    }
+
+    // Same sequence as the double 7-bag the older replays were played with
+    use rand::seq::SliceRandom;
+
+    let mut rng: StdRng = SeedableRng::from_seed(seed);
+    let mut expected = vec![];
+    for _ in 0..3 {
+        let mut shapes = crate::shape::all_shape_types();
+        shapes.shuffle(&mut rng);
+        expected.extend(shapes);
+    }
+
+    let mut bag = Bag::new(&seed, RandomizerType::Bag7);
+    let sequence: Vec<ShapeType> = (0..21).map(|_| bag.pop()).collect();
+    assert_eq!(sequence, expected);
+}
@@ -0,0 +1,1076 @@
+use std::{collections::VecDeque, time::Duration};
+
+use crate::{
+    action::Action,
+    bag::Bag,
+    garbage::Garbage,
+    gravity::{self, MAX_GRAVITY},
+    holder::Holder,
+    mode::{self, Board, GameMode, Progress, Response},
+    piece::{LockReset, Piece, TSpin},
+    replay::{ReplayData, TimedAction},
+    rotation::RotationSystem,
+    score::Score,
+    shape::ShapeType,
+    stack::{Locked, Row, Stack},
+    stats::Stats,
+};
+
+/// Length of a single step of the simulation
+pub const TICK: Duration = Duration::from_millis(1);
+
+/// Hidden rows above the stack where the pieces spawn
+const VANISH: i32 = 20;
+
+/// Gravity multiplied while soft dropping in the modes where pieces don't fall on their own,
+/// the same as on the first level of the guideline
+const SOFT_DROP_GRAVITY: f32 = 1.0 / 60.0;
+
+/// Actions that can be held during the entry delay, hold goes first,
+/// so the rotation applies to the piece taken out of the hold
+pub const INITIAL_ACTIONS: [Action; 4] = [
+    Action::HoldPiece,
+    Action::RotateClockwise,
+    Action::RotateCounterClockwise,
+    Action::Rotate180,
+];
+
+#[derive(Clone, Debug)]
+pub struct Rules {
+    pub entry_delay: Duration,
+    pub lock_delay: Duration,
+    pub clear_delay: Duration,
+    pub lock_reset: LockReset,
+    /// Multiple of the gravity while soft dropping, 0 drops the piece right onto the stack
+    pub soft_drop_factor: u32,
+}
+
+impl Default for Rules {
+    fn default() -> Rules {
+        Rules {
+            entry_delay: Duration::new(0, 0),
+            lock_delay: Duration::from_millis(500),
+            clear_delay: Duration::from_millis(250),
+            lock_reset: LockReset::Infinite,
+            soft_drop_factor: 20,
+        }
+    }
+}
+
+/// Reason why the game has ended
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum End {
+    TopOut,
+    Goal,
+    TimeUp,
+}
+
+/// Something that happened during the last update, meant for the presentation layer
+#[derive(Clone, Debug, PartialEq)]
+pub enum Event {
+    Moved,
+    Rotated,
+    Held,
+    HoldFailed,
+    /// Piece was placed, with its final column and rotation
+    Locked {
+        shape: ShapeType,
+        x: i32,
+        rotation: usize,
+        rows: i32,
+        t_spin: TSpin,
+        /// Stack is empty after clearing the rows
+        perfect_clear: bool,
+        btb: bool,
+        combo: Option<i32>,
+    },
+    RowsCleared(Vec<(i32, Row)>),
+    /// Lines of garbage left after cancelling the incoming ones
+    Attack(u32),
+    GarbageReceived(u32),
+    LevelUp(u32),
+    GameOver,
+    /// Goal of the mode was reached
+    Finished,
+    TimeUp,
+}
+
+#[derive(Clone)]
+pub struct Engine {
+    rules: Rules,
+    mode: Box<dyn GameMode>,
+
+    ticks: u32,
+    accumulator: Duration,
+
+    actions: VecDeque<Action>,
+    /// Held by the player, applied to the next piece as it spawns (IRS and IHS)
+    initial_actions: Vec<Action>,
+    replay: ReplayData,
+    playback: Option<VecDeque<TimedAction>>,
+
+    stack: Stack,
+    bag: Bag,
+    piece: Piece,
+    holder: Holder,
+    score: Score,
+    garbage: Garbage,
+    level: u32,
+    lines: u32,
+    pieces: u32,
+    stats: Stats,
+
+    end: Option<(End, u32)>,
+    falling: Duration,
+    soft_dropping: bool,
+
+    piece_entering: Option<Duration>,
+
+    events: Vec<Event>,
+}
+
+impl Engine {
+    pub fn new(seed: &[u8; 32], rules: Rules, mode: Box<dyn GameMode>) -> Engine {
+        let board = mode.board();
+        let stack = Stack::new(board.width, board.height, VANISH);
+
+        let mut bag = Bag::new(seed, mode.randomizer());
+        let piece = Piece::new(bag.pop(), mode.rotation_system(), rules.lock_reset, &stack);
+        let score = Score::new(mode.scoring().clone());
+
+        let mut engine = Engine {
+            level: 1,
+            lines: 0,
+            pieces: 0,
+            stats: Stats::default(),
+            rules,
+            mode,
+            ticks: 0,
+            accumulator: Duration::new(0, 0),
+            actions: VecDeque::new(),
+            initial_actions: vec![],
+            replay: ReplayData::new(seed),
+            playback: None,
+            stack,
+            bag,
+            piece,
+            holder: Holder::default(),
+            score,
+            garbage: Garbage::new(seed),
+            end: None,
+            falling: Duration::new(0, 0),
+            soft_dropping: false,
+            piece_entering: None,
+            events: vec![],
+        };
+
+        let settings = &mut engine.replay.header.settings;
+        settings.entry_delay = engine.rules.entry_delay.as_millis() as u32;
+        settings.lock_delay = engine.rules.lock_delay.as_millis() as u32;
+        settings.lock_reset = engine.rules.lock_reset;
+        settings.soft_drop_factor = engine.rules.soft_drop_factor;
+        settings.clear_delay = engine.rules.clear_delay.as_millis() as u32;
+        settings.mode = engine.mode.settings();
+        engine.replay.header.mode = String::from(engine.mode.name());
+
+        engine.level = engine.mode.level(&engine.progress());
+
+        let response = engine.mode.start();
+        engine.respond(response);
+        engine.insert_garbage();
+
+        engine
+    }
+
+    /// Engine that feeds itself with the actions of the replay, ignoring any other actions,
+    /// the rules and the mode are the ones the replay was recorded with.
+    /// The replay has to be validated, the decoded and the imported ones already are
+    pub fn from_replay(replay_data: &ReplayData) -> Engine {
+        let settings = &replay_data.header.settings;
+        let mode = mode::create(&replay_data.header.mode, &settings.mode)
+            .expect("replay of an unknown mode");
+
+        let mut engine = Engine::new(&replay_data.seed, settings.rules(), mode);
+        engine.replay.header = replay_data.header.clone();
+        engine.playback = Some(replay_data.actions.clone());
+        engine
+    }
+
+    pub fn action(&mut self, action: Action, immediate: bool) {
+        if self.playback.is_some() {
+            return;
+        }
+
+        if immediate {
+            self.actions.push_front(action);
+        } else {
+            self.actions.push_back(action);
+        }
+    }
+
+    /// Replaces the actions held for the next piece, only the ones in `INITIAL_ACTIONS` are kept
+    pub fn initial_actions(&mut self, actions: &[Action]) {
+        self.initial_actions = INITIAL_ACTIONS
+            .iter()
+            .filter(|action| actions.contains(action))
+            .copied()
+            .collect();
+    }
+
+    pub fn actions(&mut self, actions: &[Action]) {
+        for &action in actions {
+            self.action(action, false);
+        }
+    }
+
+    pub fn events(&mut self) -> Vec<Event> {
+        self.events.drain(..).collect()
+    }
+
+    /// Replay of the game so far, with the results in the header
+    pub fn replay_data(&self) -> ReplayData {
+        let mut replay_data = self.replay.clone();
+
+        let header = &mut replay_data.header;
+        header.score = self.score.score();
+        header.lines = self.lines;
+        header.pieces = self.pieces;
+        header.time = self.time().as_millis() as u64;
+
+        replay_data
+    }
+
+    pub fn rules(&self) -> &Rules {
+        &self.rules
+    }
+
+    pub fn mode(&self) -> &dyn GameMode {
+        self.mode.as_ref()
+    }
+
+    pub fn replaying(&self) -> bool {
+        self.playback.is_some()
+    }
+
+    pub fn ticks(&self) -> u32 {
+        self.ticks
+    }
+
+    pub fn stack(&self) -> &Stack {
+        &self.stack
+    }
+
+    pub fn stack_mut(&mut self) -> &mut Stack {
+        &mut self.stack
+    }
+
+    pub fn bag(&self) -> &Bag {
+        &self.bag
+    }
+
+    pub fn rotation_system(&self) -> RotationSystem {
+        self.mode.rotation_system()
+    }
+
+    pub fn board(&self) -> Board {
+        self.mode.board()
+    }
+
+    pub fn holder(&self) -> &Holder {
+        &self.holder
+    }
+
+    pub fn piece(&self) -> &Piece {
+        &self.piece
+    }
+
+    pub fn piece_visible(&self) -> bool {
+        self.piece_entering.is_none() && !self.game_over()
+    }
+
+    pub fn piece_entering(&self) -> bool {
+        self.piece_entering.is_some()
+    }
+
+    pub fn score(&self) -> &Score {
+        &self.score
+    }
+
+    pub fn garbage(&self) -> &Garbage {
+        &self.garbage
+    }
+
+    pub fn level(&self) -> u32 {
+        self.level
+    }
+
+    pub fn lines(&self) -> u32 {
+        self.lines
+    }
+
+    pub fn pieces(&self) -> u32 {
+        self.pieces
+    }
+
+    pub fn stats(&self) -> &Stats {
+        &self.stats
+    }
+
+    /// Pieces per second
+    pub fn pps(&self) -> f32 {
+        let time = self.time().as_secs_f32();
+        if time > 0.0 {
+            self.pieces as f32 / time
+        } else {
+            0.0
+        }
+    }
+
+    /// Plays the rest of the replay at once, without waiting for the time to pass
+    pub fn finish_replay(&mut self, length: u32) {
+        while !self.game_over() && self.ticks < length {
+            self.tick();
+        }
+    }
+
+    /// Time spent playing, stops when the game ends
+    pub fn time(&self) -> Duration {
+        TICK * self.end.map_or(self.ticks, |(_, tick)| tick)
+    }
+
+    pub fn time_left(&self) -> Option<Duration> {
+        self.mode
+            .time_limit()
+            .map(|limit| limit.checked_sub(self.time()).unwrap_or_default())
+    }
+
+    pub fn end(&self) -> Option<End> {
+        self.end.map(|(end, _)| end)
+    }
+
+    pub fn game_over(&self) -> bool {
+        self.end.is_some()
+    }
+
+    pub fn paused(&self) -> bool {
+        self.game_over() || self.stack.blocked()
+    }
+
+    /// Runs as many ticks as fit in the elapsed time, the rest is carried over
+    pub fn update(&mut self, dt: Duration) {
+        self.accumulator += dt;
+
+        while self.accumulator >= TICK {
+            self.accumulator -= TICK;
+            self.tick();
+        }
+    }
+
+    pub fn tick(&mut self) {
+        if let Some(playback) = self.playback.as_mut() {
+            while let Some(timed_action) = playback.front() {
+                if timed_action.tick != self.ticks {
+                    break;
+                }
+
+                self.actions.push_back(timed_action.action);
+                playback.pop_front();
+            }
+        }
+
+        self.step();
+
+        if !self.game_over() {
+            let progress = self.progress();
+            let response = self.mode.tick(&progress);
+            self.respond(response);
+        }
+
+        self.ticks += 1;
+    }
+
+    fn step(&mut self) {
+        if let Some(rows) = self.stack.update(TICK) {
+            self.events.push(Event::RowsCleared(rows));
+        }
+
+        if self.paused() {
+            return;
+        }
+
+        if self.piece_entering.is_none() {
+            while let Some(action) = self.actions.pop_front() {
+                self.replay.add(self.ticks, action);
+
+                if !self.process_action(action) {
+                    break;
+                }
+            }
+        }
+
+        if self.paused() {
+            return;
+        }
+
+        self.piece.update(TICK, &self.stack);
+
+        if let Some(entering) = self.piece_entering.as_mut() {
+            *entering += TICK;
+
+            if *entering >= self.rules.entry_delay {
+                self.piece_entering = None;
+                self.spawn_next();
+            }
+        } else if self.piece.locking() > self.rules.lock_delay {
+            self.lock_piece();
+        } else {
+            self.fall();
+        }
+    }
+
+    /// State of the game for the mode, valid only during a tick
+    fn progress(&self) -> Progress {
+        Progress {
+            time: TICK * (self.ticks + 1),
+            lines: self.lines,
+            level: self.level,
+            pieces: self.pieces,
+            score: self.score.score(),
+        }
+    }
+
+    fn respond(&mut self, response: Response) {
+        for lines in response.garbage {
+            self.garbage.receive(lines);
+        }
+
+        match response.end {
+            _ if self.game_over() => (),
+            Some(End::TopOut) => self.top_out(),
+            Some(End::Goal) => {
+                self.finish(End::Goal);
+                self.events.push(Event::Finished);
+            }
+            Some(End::TimeUp) => {
+                self.finish(End::TimeUp);
+                self.events.push(Event::TimeUp);
+            }
+            None => (),
+        }
+    }
+
+    fn fall(&mut self) {
+        let soft_drop = self.soft_dropping && self.rules.soft_drop_factor == 0;
+        if soft_drop || self.mode.gravity(self.level) >= MAX_GRAVITY {
+            let rows = self.piece.fall(&self.stack);
+            if self.soft_dropping {
+                self.score.soft_drop(rows);
+            }
+            return;
+        }
+
+        let interval = match self.fall_interval() {
+            Some(interval) => interval,
+            None => return,
+        };
+
+        self.falling += TICK;
+
+        while self.falling >= interval {
+            self.falling -= interval;
+
+            // The piece is resting on the stack, the lock delay takes care of it
+            if !self.piece.shift(0, 1, &self.stack) {
+                self.falling = Duration::new(0, 0);
+                break;
+            }
+
+            if self.soft_dropping {
+                self.score.soft_drop(1);
+            }
+        }
+    }
+
+    /// Time needed to fall one row, shortened while soft dropping
+    fn fall_interval(&self) -> Option<Duration> {
+        let gravity = self.mode.gravity(self.level);
+        if !self.soft_dropping {
+            return gravity::interval(gravity);
+        }
+
+        let gravity = if gravity > 0.0 {
+            gravity
+        } else {
+            SOFT_DROP_GRAVITY
+        };
+        gravity::interval(gravity * self.rules.soft_drop_factor as f32)
+    }
+
+    fn spawn(&self, shape: ShapeType) -> Piece {
+        Piece::new(
+            shape,
+            self.mode.rotation_system(),
+            self.rules.lock_reset,
+            &self.stack,
+        )
+    }
+
+    /// The initial actions can move the piece out of the way before the spawn is checked
+    fn spawn_next(&mut self) {
+        let shape = self.bag.pop();
+        self.piece = self.spawn(shape);
+        self.holder.unlock();
+
+        // Replays recorded them after the action that locked the previous piece,
+        // nothing else is left in the queue by then
+        let initial_actions: Vec<Action> = if self.playback.is_some() {
+            self.actions.drain(..).collect()
+        } else {
+            self.initial_actions.clone()
+        };
+
+        for action in initial_actions {
+            self.replay.add(self.ticks, action);
+            self.process_action(action);
+        }
+
+        if self.stack.collision(&self.piece) {
+            self.top_out();
+        } else {
+            self.reset_fall();
+        }
+    }
+
+    fn reset_fall(&mut self) {
+        match self.fall_interval() {
+            Some(interval) if self.falling > interval => self.falling -= interval,
+            _ => self.falling = Duration::new(0, 0),
+        }
+    }
+
+    fn add_lines(&mut self, rows: i32) {
+        self.lines += rows as u32;
+
+        let level = self.mode.level(&self.progress());
+        if level > self.level {
+            self.level = level;
+            self.events.push(Event::LevelUp(level));
+        }
+    }
+
+    fn lock_piece(&mut self) {
+        let t_spin = self.piece.t_spin(&self.stack);
+
+        match self.stack.lock(&self.piece, self.rules.clear_delay) {
+            Locked::Collision => self.top_out(),
+            Locked::Success(rows) => {
+                self.pieces += 1;
+
+                let perfect_clear = rows > 0 && self.stack.perfect_clear();
+                let attack = self.score.lock(rows, t_spin, perfect_clear, self.level);
+                let attack = if rows > 0 {
+                    self.add_lines(rows);
+                    self.garbage.cancel(attack as u32)
+                } else {
+                    0
+                };
+
+                self.stats
+                    .lock(rows, t_spin, perfect_clear, self.score.combo());
+
+                self.events.push(Event::Locked {
+                    shape: self.piece.shape(),
+                    x: self.piece.x,
+                    rotation: self.piece.rotation(),
+                    rows,
+                    t_spin,
+                    perfect_clear,
+                    btb: self.score.btb(),
+                    combo: self.score.combo(),
+                });
+
+                if attack > 0 {
+                    self.events.push(Event::Attack(attack));
+                }
+
+                self.piece_entering = Some(Duration::new(0, 0));
+
+                let progress = self.progress();
+                let response = self.mode.lock(&progress, rows, t_spin);
+                self.respond(response);
+
+                if rows == 0 {
+                    self.insert_garbage();
+                }
+            }
+        }
+    }
+
+    fn insert_garbage(&mut self) {
+        while let Some((lines, hole)) = self.garbage.pop(self.stack.width) {
+            if !self.stack.add_garbage(lines as usize, hole) {
+                self.top_out();
+                return;
+            }
+        }
+    }
+
+    fn finish(&mut self, end: End) {
+        // The current tick counts as played
+        if self.end.is_none() {
+            self.end = Some((end, self.ticks + 1));
+        }
+    }
+
+    fn top_out(&mut self) {
+        self.finish(End::TopOut);
+        self.stack.game_over();
+        self.events.push(Event::GameOver);
+    }
+
+    /// Returns false when the rest of the actions have to wait for the next piece
+    fn process_action(&mut self, action: Action) -> bool {
+        match action {
+            Action::MoveRight | Action::MoveLeft => {
+                let x = if action == Action::MoveRight { 1 } else { -1 };
+
+                let moved = self.piece.shift(x, 0, &self.stack);
+                if moved && self.piece.touching_floor(&self.stack) {
+                    self.reset_fall();
+                }
+
+                if moved {
+                    self.events.push(Event::Moved);
+                }
+            }
+            Action::MoveDown => {
+                if self.piece.shift(0, 1, &self.stack) {
+                    self.reset_fall();
+                    self.events.push(Event::Moved);
+                }
+            }
+            Action::RotateClockwise | Action::RotateCounterClockwise => {
+                let clockwise = action == Action::RotateClockwise;
+
+                let rotated = self.piece.rotate(clockwise, &self.stack);
+                if rotated && self.piece.touching_floor(&self.stack) {
+                    self.reset_fall();
+                }
+
+                if rotated {
+                    self.events.push(Event::Rotated);
+                }
+            }
+            Action::Rotate180 => {
+                let rotated = self.piece.rotate_180(&self.stack);
+                if rotated && self.piece.touching_floor(&self.stack) {
+                    self.reset_fall();
+                }
+
+                if rotated {
+                    self.events.push(Event::Rotated);
+                }
+            }
+            Action::SonicDrop => {
+                let rows = self.piece.fall(&self.stack);
+                if rows > 0 {
+                    self.reset_fall();
+                    self.score.soft_drop(rows);
+                }
+            }
+            Action::SoftDropStart => self.soft_dropping = true,
+            Action::SoftDropStop => self.soft_dropping = false,
+            Action::HardDrop => {
+                let rows = self.piece.fall(&self.stack);
+                self.score.hard_drop(rows);
+                self.lock_piece();
+
+                return false;
+            }
+            Action::HoldPiece => {
+                let rotation_system = self.mode.rotation_system();
+                if !self.mode.board().hold {
+                    self.events.push(Event::HoldFailed);
+                } else if let Some(shape) =
+                    self.holder
+                        .hold(self.piece.shape(), rotation_system, &mut self.bag)
+                {
+                    self.piece = self.spawn(shape);
+                    self.events.push(Event::Held);
+                } else {
+                    self.events.push(Event::HoldFailed);
+                }
+            }
+            Action::Garbage(lines) => {
+                self.garbage.receive(lines);
+                self.events.push(Event::GarbageReceived(lines));
+            }
+            Action::GameOver => {
+                self.top_out();
+
+                return false;
+            }
+        };
+
+        true
+    }
+}
+
+#[test]
+fn engine_test() {
+    use crate::mode::Marathon;
+
+    let seed = [0; 32];
+    let mut engine = Engine::new(&seed, Rules::default(), Box::new(Marathon::default()));
+
+    let next = *engine.bag().peek(1).next().unwrap();
+    let piece = engine.piece().clone();
+
+    engine.action(Action::HardDrop, false);
+    engine.update(Duration::from_millis(16));
+
+    assert!(engine.score().score() > 0);
+    assert_eq!(
+        engine.events(),
+        vec![Event::Locked {
+            shape: piece.shape(),
+            x: piece.x,
+            rotation: 0,
+            rows: 0,
+            t_spin: TSpin::None,
+            perfect_clear: false,
+            btb: false,
+            combo: None,
+        }]
+    );
+
+    assert_ne!(engine.stack().grid(), Stack::new(10, 20, 20).grid());
+    assert_eq!(engine.piece().shape(), next);
+    assert_eq!(engine.ticks(), 16);
+    assert_eq!(engine.replay_data().actions.len(), 1);
+}
+
+#[test]
+fn replay_test() {
+    use crate::mode::Marathon;
+
+    let seed = [7; 32];
+    let rules = Rules {
+        lock_delay: Duration::from_millis(300),
+        lock_reset: LockReset::Move,
+        ..Rules::default()
+    };
+    let mode = Marathon {
+        start_level: 4,
+        ..Marathon::default()
+    };
+    let mut engine = Engine::new(&seed, rules, Box::new(mode));
+
+    let actions = [
+        Action::MoveLeft,
+        Action::RotateClockwise,
+        Action::HardDrop,
+        Action::HoldPiece,
+        Action::MoveRight,
+        Action::MoveRight,
+        Action::SonicDrop,
+        Action::SoftDropStart,
+        Action::RotateCounterClockwise,
+        Action::MoveDown,
+        Action::SoftDropStop,
+    ];
+
+    for (i, &action) in actions.iter().cycle().take(300).enumerate() {
+        engine.action(action, false);
+        engine.update(Duration::from_micros(5_000 + 7_919 * (i as u64 % 13)));
+    }
+
+    while !engine.game_over() && engine.ticks() < 1_000_000 {
+        engine.update(Duration::from_millis(33));
+    }
+
+    let mut replay = Engine::from_replay(&engine.replay_data());
+    while replay.ticks() < engine.ticks() {
+        replay.update(Duration::from_micros(16_667));
+    }
+
+    assert!(engine.game_over());
+    assert_eq!(replay.game_over(), engine.game_over());
+    assert_eq!(replay.score().score(), engine.score().score());
+    assert_eq!(replay.level(), engine.level());
+    assert_eq!(replay.rules().lock_delay, engine.rules().lock_delay);
+    assert_eq!(replay.rules().lock_reset, engine.rules().lock_reset);
+    assert_eq!(replay.stack().grid(), engine.stack().grid());
+}
+
+#[test]
+fn initial_actions_test() {
+    use crate::mode::Marathon;
+
+    // Pieces reach the floor on the tick after they spawn
+    let mode = Marathon {
+        gravity: gravity::Gravity::new(vec![MAX_GRAVITY]),
+        ..Marathon::default()
+    };
+    let mut engine = Engine::new(&[5; 32], Rules::default(), Box::new(mode));
+    engine.initial_actions(&[Action::RotateClockwise, Action::MoveLeft]);
+
+    engine.action(Action::HardDrop, false);
+    engine.tick();
+    assert_eq!(engine.piece().rotation(), 1);
+
+    engine.tick();
+    assert!(engine.piece().clone().touching_floor(engine.stack()));
+
+    let mut replay = Engine::from_replay(&engine.replay_data());
+    replay.finish_replay(engine.ticks());
+    assert_eq!(replay.piece().rotation(), 1);
+    assert_eq!(
+        (replay.piece().x, replay.piece().y),
+        (engine.piece().x, engine.piece().y)
+    );
+
+    // The next piece spawns into the blocks placed during the entry delay, unless it is turned
+    let rules = Rules {
+        entry_delay: Duration::from_millis(100),
+        ..Rules::default()
+    };
+    let mut engine = Engine::new(&[5; 32], rules, Box::new(Marathon::default()));
+    engine.action(Action::HardDrop, false);
+    engine.tick();
+    assert!(engine.piece_entering());
+
+    let next = *engine.bag().peek(1).next().unwrap();
+    assert_ne!(next, ShapeType::O);
+
+    let spawned = engine.spawn(next);
+    let mut rotated = spawned.clone();
+    assert!(rotated.rotate(true, engine.stack()));
+
+    let cells = |piece: &Piece| {
+        let grid = piece.grid();
+        let mut cells = vec![];
+        for (y, row) in grid.grid.iter().enumerate() {
+            for (x, &cell) in row.iter().enumerate() {
+                if cell != 0 {
+                    cells.push(((piece.x + x as i32) as usize, (piece.y + y as i32) as usize));
+                }
+            }
+        }
+        cells
+    };
+
+    let rotated_cells = cells(&rotated);
+    for (x, y) in cells(&spawned) {
+        if !rotated_cells.contains(&(x, y)) {
+            engine.stack_mut().place_random(x, y);
+        }
+    }
+
+    let mut without = engine.clone();
+    while without.ticks() < 200 {
+        without.tick();
+    }
+    assert_eq!(without.end(), Some(End::TopOut));
+
+    engine.initial_actions(&[Action::RotateClockwise]);
+    while engine.ticks() < 200 {
+        engine.tick();
+    }
+    assert!(!engine.game_over());
+    assert_eq!(engine.piece().shape(), next);
+    assert_eq!(engine.piece().rotation(), 1);
+}
+
+#[test]
+fn level_test() {
+    use crate::mode::Marathon;
+
+    let seed = [0; 32];
+    let mut engine = Engine::new(
+        &seed,
+        Rules::default(),
+        Box::new(Marathon {
+            lines_per_level: 4,
+            ..Marathon::default()
+        }),
+    );
+
+    engine.stack_mut().debug_tetris();
+    engine.piece = engine.spawn(crate::shape::ShapeType::I);
+
+    engine.action(Action::RotateClockwise, false);
+    for _ in 0..4 {
+        engine.action(Action::MoveRight, false);
+    }
+    engine.action(Action::HardDrop, false);
+    engine.tick();
+
+    assert_eq!(engine.lines(), 4);
+    assert_eq!(engine.level(), 2);
+    assert!(engine.events().contains(&Event::LevelUp(2)));
+}
+
+#[test]
+fn legacy_replay_test() {
+    use flate2::{write::GzEncoder, Compression};
+    use serde::Serialize;
+    use std::io::Write;
+
+    #[derive(Serialize)]
+    struct Legacy {
+        seed: [u8; 32],
+        actions: Vec<(u32, Duration)>,
+    }
+
+    // RotateClockwise, four MoveRight and HardDrop in the old numbering, a second apart
+    let round = [3, 0, 0, 0, 0, 5];
+    let legacy = Legacy {
+        seed: [0; 32],
+        actions: (0..3)
+            .flat_map(|_| round.iter().enumerate())
+            .map(|(i, &action)| (action, Duration::from_millis(if i == 0 { 1000 } else { 0 })))
+            .collect(),
+    };
+
+    let mut writer = GzEncoder::new(Vec::new(), Compression::best());
+    writer
+        .write_all(&bincode::serialize(&legacy).unwrap())
+        .unwrap();
+
+    let upgraded = ReplayData::decode(&writer.finish().unwrap()).unwrap();
+    let decoded = ReplayData::decode(&upgraded.encode()).unwrap();
+    assert_eq!(decoded.header, upgraded.header);
+    assert_eq!(decoded.actions, upgraded.actions);
+
+    let mut engine = Engine::from_replay(&decoded);
+    for round in 1..=3 {
+        while engine.ticks() < round * 1000 {
+            engine.tick();
+        }
+
+        engine.stack_mut().debug_tetris();
+        engine.piece = engine.spawn(crate::shape::ShapeType::I);
+        engine.tick();
+    }
+
+    assert_eq!(engine.lines(), 12);
+    assert_eq!(engine.level(), 1);
+    assert_eq!(engine.mode.gravity(engine.level()), 1.0 / 60.0);
+}
+
+#[test]
+fn line_goal_test() {
+    use crate::mode::Sprint;
+
+    let seed = [0; 32];
+    let mut engine = Engine::new(
+        &seed,
+        Rules::default(),
+        Box::new(Sprint {
+            lines: 4,
+            ..Sprint::default()
+        }),
+    );
+
+    engine.stack_mut().debug_tetris();
+    engine.piece = engine.spawn(crate::shape::ShapeType::I);
+
+    engine.action(Action::RotateClockwise, false);
+    for _ in 0..4 {
+        engine.action(Action::MoveRight, false);
+    }
+    engine.action(Action::HardDrop, false);
+    engine.update(Duration::from_millis(10));
+
+    assert_eq!(engine.end(), Some(End::Goal));
+    assert_eq!(engine.pieces(), 1);
+    assert_eq!(engine.stats().tetrises, 1);
+    assert_eq!(engine.time(), TICK);
+
+    engine.update(Duration::from_secs(1));
+    assert_eq!(engine.time(), TICK);
+}
+
+#[test]
+fn time_limit_test() {
+    use crate::mode::Ultra;
+
+    let seed = [0; 32];
+    let mut engine = Engine::new(
+        &seed,
+        Rules::default(),
+        Box::new(Ultra {
+            time_limit: Duration::from_secs(2),
+            ..Ultra::default()
+        }),
+    );
+
+    engine.update(Duration::from_millis(1500));
+    assert_eq!(engine.time_left(), Some(Duration::from_millis(500)));
+    assert!(!engine.game_over());
+
+    engine.update(Duration::from_secs(1));
+    assert_eq!(engine.end(), Some(End::TimeUp));
+    assert_eq!(engine.time(), Duration::from_secs(2));
+    assert_eq!(engine.time_left(), Some(Duration::new(0, 0)));
+    assert!(engine.events().contains(&Event::TimeUp));
+}
+
+#[test]
+fn soft_drop_test() {
+    use crate::mode::Marathon;
+
+    for &(soft_drop_factor, rows) in &[(20, 10), (5, 2), (0, 20)] {
+        let rules = Rules {
+            soft_drop_factor,
+            ..Rules::default()
+        };
+        let mut engine = Engine::new(&[0; 32], rules, Box::new(Marathon::default()));
+        let y = engine.piece().y;
+
+        engine.action(Action::SoftDropStart, false);
+        engine.update(Duration::from_millis(500));
+        assert_eq!(engine.piece().y - y, rows);
+        assert_eq!(engine.score().score(), rows);
+
+        if soft_drop_factor > 0 {
+            engine.action(Action::SoftDropStop, false);
+            engine.update(Duration::from_millis(500));
+            assert!(engine.piece().y - y <= rows + 1);
+        }
+    }
+}
+
+#[test]
+fn board_test() {
+    use crate::mode::{CommonSettings, Marathon};
+
+    let board = Board {
+        width: 6,
+        height: 12,
+        hold: false,
+        ..Board::default()
+    };
+    let mode = Marathon {
+        common: CommonSettings {
+            board,
+            ..CommonSettings::default()
+        },
+        ..Marathon::default()
+    };
+    let mut engine = Engine::new(&[3; 32], Rules::default(), Box::new(mode));
+    assert_eq!((engine.stack().width, engine.stack().height), (6, 12));
+
+    engine.action(Action::HoldPiece, false);
+    engine.update(Duration::from_millis(10));
+    assert!(engine.events().contains(&Event::HoldFailed));
+    assert!(engine.holder().shape().is_none());
+
+    engine.action(Action::HardDrop, false);
+    engine.update(Duration::from_millis(10));
+
+    let replay = Engine::from_replay(&engine.replay_data());
+    assert_eq!(replay.board(), board);
+    assert_eq!(replay.stack().width, 6);
+}
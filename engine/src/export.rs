@@ -0,0 +1,158 @@
+//! Human-readable form of the replays, meant for diffing and analyzing them with other tools.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    replay::{Header, ReplayData, ReplayError, TimedAction, VERSION},
+    shape::ShapeType,
+    Engine, Event,
+};
+
+/// Where and how a piece ended up, found by playing the replay
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PieceSummary {
+    /// Tick the piece was locked on
+    pub tick: u32,
+    pub shape: ShapeType,
+    pub x: i32,
+    pub rotation: usize,
+    pub lines: i32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct JsonReplay {
+    version: u16,
+    header: Header,
+    /// Hexadecimal
+    seed: String,
+    actions: Vec<TimedAction>,
+    /// Only informative, it is ignored on import
+    #[serde(default)]
+    pieces: Vec<PieceSummary>,
+}
+
+/// Plays the whole replay and lists every locked piece
+pub fn summary(replay_data: &ReplayData) -> Vec<PieceSummary> {
+    let mut engine = Engine::from_replay(replay_data);
+    let length = replay_data.length();
+    let mut pieces = vec![];
+
+    while !engine.game_over() && engine.ticks() < length {
+        engine.tick();
+
+        for event in engine.events() {
+            if let Event::Locked {
+                shape,
+                x,
+                rotation,
+                rows,
+                ..
+            } = event
+            {
+                pieces.push(PieceSummary {
+                    tick: engine.ticks() - 1,
+                    shape,
+                    x,
+                    rotation,
+                    lines: rows,
+                });
+            }
+        }
+    }
+
+    pieces
+}
+
+pub fn to_json(replay_data: &ReplayData) -> String {
+    let json = JsonReplay {
+        version: VERSION,
+        header: replay_data.header.clone(),
+        seed: replay_data
+            .seed
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect(),
+        actions: replay_data.actions.iter().copied().collect(),
+        pieces: summary(replay_data),
+    };
+
+    serde_json::to_string_pretty(&json).unwrap()
+}
+
+pub fn from_json(bytes: &[u8]) -> Result<ReplayData, ReplayError> {
+    let json: JsonReplay = serde_json::from_slice(bytes).map_err(ReplayError::Json)?;
+    if json.version > VERSION {
+        return Err(ReplayError::UnsupportedVersion(json.version));
+    } else if json.version < VERSION {
+        return Err(ReplayError::OutdatedVersion(json.version));
+    }
+
+    if json
+        .actions
+        .windows(2)
+        .any(|pair| pair[1].tick < pair[0].tick)
+    {
+        return Err(ReplayError::UnorderedActions);
+    }
+
+    let mut seed = [0; 32];
+    if json.seed.len() != seed.len() * 2 || !json.seed.is_ascii() {
+        return Err(ReplayError::InvalidSeed);
+    }
+
+    for (i, byte) in seed.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&json.seed[i * 2..i * 2 + 2], 16)
+            .map_err(|_| ReplayError::InvalidSeed)?;
+    }
+
+    let mut replay_data = ReplayData::new(&seed);
+    replay_data.header = json.header;
+    replay_data.actions = json.actions.into_iter().collect();
+
+    replay_data.validate()?;
+    Ok(replay_data)
+}
+
+#[test]
+fn export_test() {
+    use crate::{action::Action, engine::Rules, mode::Marathon};
+    use std::time::Duration;
+
+    let mut engine = Engine::new(&[9; 32], Rules::default(), Box::new(Marathon::default()));
+    for action in &[Action::MoveLeft, Action::HardDrop, Action::RotateClockwise] {
+        engine.action(*action, false);
+        engine.update(Duration::from_millis(700));
+    }
+    engine.action(Action::HardDrop, false);
+    engine.update(Duration::from_millis(100));
+
+    let replay_data = engine.replay_data();
+    let pieces = summary(&replay_data);
+    assert_eq!(pieces.len(), 2);
+    assert_eq!(pieces[0].lines, 0);
+    assert_eq!(pieces[1].rotation, 1);
+
+    let json = to_json(&replay_data);
+    let imported = from_json(json.as_bytes()).unwrap();
+    assert_eq!(imported.seed, replay_data.seed);
+    assert_eq!(imported.header, replay_data.header);
+    assert_eq!(imported.actions, replay_data.actions);
+
+    assert!(from_json(json.replace("\"seed\": \"09", "\"seed\": \"x9").as_bytes()).is_err());
+
+    let older = json.replace(
+        &format!("\"version\": {}", VERSION),
+        &format!("\"version\": {}", VERSION - 1),
+    );
+    match from_json(older.as_bytes()) {
+        Err(ReplayError::OutdatedVersion(version)) => assert_eq!(version, VERSION - 1),
+        _ => panic!("outdated JSON replay was imported"),
+    }
+
+    let mut unordered = replay_data.clone();
+    unordered.actions.swap(0, 1);
+    match from_json(to_json(&unordered).as_bytes()) {
+        Err(ReplayError::UnorderedActions) => (),
+        _ => panic!("unordered actions were imported"),
+    }
+}
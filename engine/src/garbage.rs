@@ -0,0 +1,87 @@
+use std::collections::VecDeque;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+/// Lines sent for the number of consecutive clears, the last entry is used for longer combos
+pub const COMBO_TABLE: [i32; 12] = [0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 4, 5];
+
+/// Lines sent for emptying the whole stack, on top of the ones for the clear
+pub const PERFECT_CLEAR_GARBAGE: i32 = 10;
+
+/// Incoming garbage waiting to be inserted into the stack,
+/// lines received in the same attack share the hole column
+#[derive(Clone)]
+pub struct Garbage {
+    incoming: VecDeque<u32>,
+    rng: StdRng,
+}
+
+impl Garbage {
+    pub fn new(seed: &[u8; 32]) -> Garbage {
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(&seed[..8]);
+
+        Garbage {
+            incoming: VecDeque::new(),
+            rng: StdRng::seed_from_u64(u64::from_le_bytes(bytes)),
+        }
+    }
+
+    pub fn receive(&mut self, lines: u32) {
+        if lines > 0 {
+            self.incoming.push_back(lines);
+        }
+    }
+
+    /// Cancels incoming garbage with the attack, returns lines left to send
+    pub fn cancel(&mut self, attack: u32) -> u32 {
+        let mut attack = attack;
+
+        while let Some(lines) = self.incoming.front_mut() {
+            if attack == 0 {
+                break;
+            }
+
+            if *lines > attack {
+                *lines -= attack;
+                attack = 0;
+            } else {
+                attack -= *lines;
+                self.incoming.pop_front();
+            }
+        }
+
+        attack
+    }
+
+    pub fn pending(&self) -> u32 {
+        self.incoming.iter().sum()
+    }
+
+    /// Next attack to insert as number of lines and the hole column
+    pub fn pop(&mut self, width: i32) -> Option<(u32, usize)> {
+        let lines = self.incoming.pop_front()?;
+        let hole = self.rng.gen_range(0, width as usize);
+
+        Some((lines, hole))
+    }
+}
+
+#[test]
+fn garbage_test() {
+    let mut garbage = Garbage::new(&[0; 32]);
+    garbage.receive(2);
+    garbage.receive(3);
+    assert_eq!(garbage.pending(), 5);
+
+    assert_eq!(garbage.cancel(3), 0);
+    assert_eq!(garbage.pending(), 2);
+    assert_eq!(garbage.cancel(4), 2);
+    assert_eq!(garbage.pending(), 0);
+
+    garbage.receive(4);
+    let (lines, hole) = garbage.pop(10).unwrap();
+    assert_eq!(lines, 4);
+    assert!(hole < 10);
+    assert!(garbage.pop(10).is_none());
+}
@@ -0,0 +1,70 @@
+use std::time::Duration;
+
+/// Gravity at which pieces fall all the way down as soon as they appear
+pub const MAX_GRAVITY: f32 = 20.0;
+
+/// Gravity in G (rows per frame at 60 frames per second) for each level
+/// starting from the first one, the last entry is used for all higher levels
+#[derive(Clone, Debug, PartialEq)]
+pub struct Gravity {
+    pub levels: Vec<f32>,
+}
+
+impl Gravity {
+    pub fn new(levels: Vec<f32>) -> Gravity {
+        Gravity { levels }
+    }
+
+    /// Curve from the guideline, reaching 20G on level 20
+    pub fn guideline() -> Gravity {
+        let mut levels: Vec<f32> = (0..19)
+            .map(|level| {
+                let seconds = (0.8 - level as f64 * 0.007).powi(level);
+                (1.0 / (seconds * 60.0)) as f32
+            })
+            .collect();
+
+        levels.push(MAX_GRAVITY);
+        Gravity { levels }
+    }
+
+    pub fn get(&self, level: u32) -> f32 {
+        let index = (level.max(1) - 1) as usize;
+        self.levels
+            .get(index)
+            .or_else(|| self.levels.last())
+            .copied()
+            .unwrap_or(0.0)
+    }
+
+    pub fn interval(&self, level: u32) -> Option<Duration> {
+        interval(self.get(level))
+    }
+}
+
+/// Time needed to fall one row, `None` if pieces don't fall at all
+pub fn interval(gravity: f32) -> Option<Duration> {
+    if gravity > 0.0 {
+        Some(Duration::from_secs_f64(1.0 / 60.0 / f64::from(gravity)))
+    } else {
+        None
+    }
+}
+
+impl Default for Gravity {
+    fn default() -> Gravity {
+        Gravity::guideline()
+    }
+}
+
+#[test]
+fn gravity_test() {
+    let gravity = Gravity::guideline();
+
+    let interval = gravity.interval(1).unwrap();
+    assert!((interval.as_secs_f64() - 1.0).abs() < 1e-6);
+    assert!(gravity.get(2) > gravity.get(1));
+    assert_eq!(gravity.get(20), MAX_GRAVITY);
+    assert_eq!(gravity.get(99), MAX_GRAVITY);
+    assert_eq!(Gravity::new(vec![]).interval(1), None);
+}
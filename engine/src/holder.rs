@@ -0,0 +1,42 @@
+use crate::{
+    bag::Bag,
+    rotation::RotationSystem,
+    shape::{Shape, ShapeType},
+};
+
+#[derive(Default, Clone)]
+pub struct Holder {
+    shape: Option<Shape>,
+    locked: bool,
+}
+
+impl Holder {
+    pub fn hold(
+        &mut self,
+        shape_type: ShapeType,
+        rotation_system: RotationSystem,
+        bag: &mut Bag,
+    ) -> Option<ShapeType> {
+        if self.locked {
+            return None;
+        }
+
+        self.locked = true;
+
+        let mut swap = Some(Shape::new(shape_type, rotation_system));
+        std::mem::swap(&mut self.shape, &mut swap);
+
+        match swap {
+            None => Some(bag.pop()),
+            Some(s) => Some(s.shape_type),
+        }
+    }
+
+    pub fn unlock(&mut self) {
+        self.locked = false;
+    }
+
+    pub fn shape(&self) -> Option<&Shape> {
+        self.shape.as_ref()
+    }
+}
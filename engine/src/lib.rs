@@ -0,0 +1,25 @@
+//! Rules of the game without any windowing, rendering or audio.
+//!
+//! The [`Engine`] is advanced by explicit time steps and reports everything
+//! that happened through [`Event`]s, so it can be driven by the game itself,
+//! by tests, bots or servers.
+
+pub mod action;
+pub mod bag;
+pub mod engine;
+pub mod export;
+pub mod garbage;
+pub mod gravity;
+pub mod holder;
+pub mod mode;
+pub mod piece;
+pub mod playback;
+pub mod randomizer;
+pub mod replay;
+pub mod rotation;
+pub mod score;
+pub mod shape;
+pub mod stack;
+pub mod stats;
+
+pub use crate::engine::{End, Engine, Event, Rules};
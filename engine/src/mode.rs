@@ -0,0 +1,385 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    engine::End, gravity::Gravity, piece::TSpin, randomizer::RandomizerType,
+    rotation::RotationSystem, score::ScoreTable,
+};
+
+/// State of the game passed to the mode
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Progress {
+    /// Time played including the current tick
+    pub time: Duration,
+    pub lines: u32,
+    pub level: u32,
+    pub pieces: u32,
+    pub score: i32,
+}
+
+/// Requests of the mode to the engine
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Response {
+    /// Lines of garbage to add to the incoming queue, every entry gets its own hole
+    pub garbage: Vec<u32>,
+    pub end: Option<End>,
+}
+
+impl Response {
+    pub fn end(end: End) -> Response {
+        Response {
+            end: Some(end),
+            ..Response::default()
+        }
+    }
+}
+
+pub const MIN_WIDTH: i32 = 4;
+pub const MAX_WIDTH: i32 = 20;
+pub const MIN_HEIGHT: i32 = 10;
+pub const MAX_HEIGHT: i32 = 30;
+pub const MAX_BUFFER: i32 = 4;
+/// Most next pieces the preview can show
+pub const MAX_PREVIEW: u32 = 7;
+
+/// Size of the stack and the pieces shown around it
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct Board {
+    pub width: i32,
+    pub height: i32,
+    /// Rows above the stack that are still drawn
+    pub buffer: i32,
+    /// Next pieces shown, up to `MAX_PREVIEW`
+    pub preview: u32,
+    pub hold: bool,
+}
+
+impl Default for Board {
+    fn default() -> Board {
+        Board {
+            width: 10,
+            height: 20,
+            buffer: 1,
+            preview: 6,
+            hold: true,
+        }
+    }
+}
+
+impl Board {
+    pub fn valid(&self) -> bool {
+        self.clamped() == *self
+    }
+
+    /// Brings every value into its limits
+    pub fn clamped(self) -> Board {
+        Board {
+            width: self.width.clamp(MIN_WIDTH, MAX_WIDTH),
+            height: self.height.clamp(MIN_HEIGHT, MAX_HEIGHT),
+            buffer: self.buffer.clamp(0, MAX_BUFFER),
+            preview: self.preview.min(MAX_PREVIEW),
+            hold: self.hold,
+        }
+    }
+}
+
+/// Rules of the game every mode is played with
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct CommonSettings {
+    pub scoring: ScoreTable,
+    pub rotation_system: RotationSystem,
+    pub randomizer: RandomizerType,
+    pub board: Board,
+}
+
+/// Parameters of all modes, each mode uses only the ones it needs
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ModeSettings {
+    pub start_level: u32,
+    /// Lines needed for the next level, 0 keeps the starting level
+    pub lines_per_level: u32,
+    pub gravity: Vec<f32>,
+    pub line_goal: u32,
+    /// Time limit in milliseconds
+    pub time_limit: u64,
+    pub common: CommonSettings,
+}
+
+impl Default for ModeSettings {
+    fn default() -> ModeSettings {
+        ModeSettings {
+            start_level: 1,
+            lines_per_level: default_lines_per_level(),
+            gravity: Gravity::guideline().levels,
+            line_goal: 40,
+            time_limit: 120_000,
+            common: CommonSettings::default(),
+        }
+    }
+}
+
+fn default_lines_per_level() -> u32 {
+    10
+}
+
+/// Goals, speed, garbage and end conditions of the game
+pub trait GameMode {
+    fn name(&self) -> &'static str;
+
+    fn box_clone(&self) -> Box<dyn GameMode>;
+
+    /// Parameters needed to create the same mode again
+    fn settings(&self) -> ModeSettings {
+        ModeSettings::default()
+    }
+
+    /// Called before the first piece appears
+    fn start(&mut self) -> Response {
+        Response::default()
+    }
+
+    /// Called after every tick of the engine
+    fn tick(&mut self, _progress: &Progress) -> Response {
+        Response::default()
+    }
+
+    /// Called after a piece was locked, the progress already includes the cleared rows
+    fn lock(&mut self, _progress: &Progress, _rows: i32, _t_spin: TSpin) -> Response {
+        Response::default()
+    }
+
+    fn level(&self, _progress: &Progress) -> u32 {
+        1
+    }
+
+    /// Gravity in G on the level
+    fn gravity(&self, level: u32) -> f32;
+
+    fn common(&self) -> &CommonSettings;
+
+    fn scoring(&self) -> &ScoreTable {
+        &self.common().scoring
+    }
+
+    fn rotation_system(&self) -> RotationSystem {
+        self.common().rotation_system
+    }
+
+    fn randomizer(&self) -> RandomizerType {
+        self.common().randomizer
+    }
+
+    fn board(&self) -> Board {
+        self.common().board
+    }
+
+    fn line_goal(&self) -> Option<u32> {
+        None
+    }
+
+    fn time_limit(&self) -> Option<Duration> {
+        None
+    }
+}
+
+impl Clone for Box<dyn GameMode> {
+    fn clone(&self) -> Box<dyn GameMode> {
+        self.box_clone()
+    }
+}
+
+/// Gravity of the first level, one row per second
+const BASE_GRAVITY: f32 = 1.0 / 60.0;
+
+/// Endless game that speeds up with levels
+#[derive(Clone, Debug)]
+pub struct Marathon {
+    pub start_level: u32,
+    pub lines_per_level: u32,
+    pub gravity: Gravity,
+    pub common: CommonSettings,
+}
+
+impl Default for Marathon {
+    fn default() -> Marathon {
+        Marathon {
+            start_level: 1,
+            lines_per_level: default_lines_per_level(),
+            gravity: Gravity::guideline(),
+            common: CommonSettings::default(),
+        }
+    }
+}
+
+impl GameMode for Marathon {
+    fn name(&self) -> &'static str {
+        "Marathon"
+    }
+
+    fn box_clone(&self) -> Box<dyn GameMode> {
+        Box::new(self.clone())
+    }
+
+    fn settings(&self) -> ModeSettings {
+        ModeSettings {
+            start_level: self.start_level,
+            lines_per_level: self.lines_per_level,
+            gravity: self.gravity.levels.clone(),
+            common: self.common.clone(),
+            ..ModeSettings::default()
+        }
+    }
+
+    fn level(&self, progress: &Progress) -> u32 {
+        self.start_level.max(1)
+            + progress
+                .lines
+                .checked_div(self.lines_per_level)
+                .unwrap_or(0)
+    }
+
+    fn gravity(&self, level: u32) -> f32 {
+        self.gravity.get(level)
+    }
+
+    fn common(&self) -> &CommonSettings {
+        &self.common
+    }
+}
+
+/// Clear the lines as fast as possible
+#[derive(Clone, Debug)]
+pub struct Sprint {
+    pub lines: u32,
+    pub common: CommonSettings,
+}
+
+impl Default for Sprint {
+    fn default() -> Sprint {
+        Sprint {
+            lines: 40,
+            common: CommonSettings::default(),
+        }
+    }
+}
+
+impl GameMode for Sprint {
+    fn name(&self) -> &'static str {
+        "Sprint"
+    }
+
+    fn box_clone(&self) -> Box<dyn GameMode> {
+        Box::new(self.clone())
+    }
+
+    fn settings(&self) -> ModeSettings {
+        ModeSettings {
+            line_goal: self.lines,
+            common: self.common.clone(),
+            ..ModeSettings::default()
+        }
+    }
+
+    fn lock(&mut self, progress: &Progress, _rows: i32, _t_spin: TSpin) -> Response {
+        if progress.lines >= self.lines {
+            Response::end(End::Goal)
+        } else {
+            Response::default()
+        }
+    }
+
+    fn gravity(&self, _level: u32) -> f32 {
+        BASE_GRAVITY
+    }
+
+    fn common(&self) -> &CommonSettings {
+        &self.common
+    }
+
+    fn line_goal(&self) -> Option<u32> {
+        Some(self.lines)
+    }
+}
+
+/// Score as much as possible before the time runs out
+#[derive(Clone, Debug)]
+pub struct Ultra {
+    pub time_limit: Duration,
+    pub common: CommonSettings,
+}
+
+impl Default for Ultra {
+    fn default() -> Ultra {
+        Ultra {
+            time_limit: Duration::from_secs(120),
+            common: CommonSettings::default(),
+        }
+    }
+}
+
+impl GameMode for Ultra {
+    fn name(&self) -> &'static str {
+        "Ultra"
+    }
+
+    fn box_clone(&self) -> Box<dyn GameMode> {
+        Box::new(self.clone())
+    }
+
+    fn settings(&self) -> ModeSettings {
+        ModeSettings {
+            time_limit: self.time_limit.as_millis() as u64,
+            common: self.common.clone(),
+            ..ModeSettings::default()
+        }
+    }
+
+    fn tick(&mut self, progress: &Progress) -> Response {
+        if progress.time >= self.time_limit {
+            Response::end(End::TimeUp)
+        } else {
+            Response::default()
+        }
+    }
+
+    fn gravity(&self, _level: u32) -> f32 {
+        BASE_GRAVITY
+    }
+
+    fn common(&self) -> &CommonSettings {
+        &self.common
+    }
+
+    fn time_limit(&self) -> Option<Duration> {
+        Some(self.time_limit)
+    }
+}
+
+pub const MODES: [&str; 3] = ["Marathon", "Sprint", "Ultra"];
+
+/// Creates the mode by its name, `None` if there is no such mode
+pub fn create(name: &str, settings: &ModeSettings) -> Option<Box<dyn GameMode>> {
+    let common = settings.common.clone();
+    let mode: Box<dyn GameMode> = match name {
+        "Marathon" => Box::new(Marathon {
+            start_level: settings.start_level,
+            lines_per_level: settings.lines_per_level,
+            gravity: Gravity::new(settings.gravity.clone()),
+            common,
+        }),
+        "Sprint" => Box::new(Sprint {
+            lines: settings.line_goal,
+            common,
+        }),
+        "Ultra" => Box::new(Ultra {
+            time_limit: Duration::from_millis(settings.time_limit),
+            common,
+        }),
+        _ => return None,
+    };
+
+    Some(mode)
+}
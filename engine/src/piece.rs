@@ -0,0 +1,473 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    rotation::RotationSystem,
+    shape::{Kick, Shape, ShapeGrid, ShapeType},
+    stack::Stack,
+};
+
+#[derive(Clone, PartialEq)]
+pub enum Movement {
+    None,
+    Shift,
+    Rotate,
+    Rotate180,
+}
+
+/// What restarts the lock delay of a piece resting on the stack
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, Default, PartialEq)]
+pub enum LockReset {
+    /// Every move and rotation, the only policy of the older versions
+    #[default]
+    Infinite,
+    /// Moves and rotations on the stack, `MOVE_RESETS` times until the piece falls lower
+    Move,
+    /// Only falling lower than before
+    Step,
+    /// Nothing, the delay runs whenever the piece rests on the stack
+    None,
+}
+
+pub const LOCK_RESETS: [LockReset; 4] = [
+    LockReset::Infinite,
+    LockReset::Move,
+    LockReset::Step,
+    LockReset::None,
+];
+
+pub const MOVE_RESETS: u32 = 15;
+
+impl LockReset {
+    pub fn name(self) -> &'static str {
+        match self {
+            LockReset::Infinite => "Infinite",
+            LockReset::Move => "Move reset",
+            LockReset::Step => "Step reset",
+            LockReset::None => "None",
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum TSpin {
+    None,
+    /// Only one of the corners the T points at is occupied
+    Mini,
+    Full,
+}
+
+#[derive(Clone)]
+pub struct Piece {
+    shape: Shape,
+    pub x: i32,
+    pub y: i32,
+    rotation: usize,
+    last_movement: Movement,
+    /// Index of the kick used by the last rotation, `None` when it didn't need one
+    kick: Option<usize>,
+    locking: Duration,
+    lock_reset: LockReset,
+    /// Lock delay resets used since the piece reached its lowest row
+    resets: u32,
+    lowest: i32,
+}
+
+impl Piece {
+    pub fn new(
+        shape_type: ShapeType,
+        rotation_system: RotationSystem,
+        lock_reset: LockReset,
+        stack: &Stack,
+    ) -> Piece {
+        let mut piece = Piece {
+            shape: Shape::new(shape_type, rotation_system),
+            x: 0,
+            y: 0,
+            rotation: 0,
+            last_movement: Movement::None,
+            kick: None,
+            locking: Duration::new(0, 0),
+            lock_reset,
+            resets: 0,
+            lowest: 0,
+        };
+
+        piece.reset(stack);
+        piece
+    }
+
+    pub fn update(&mut self, dt: Duration, stack: &Stack) {
+        if self.collision(0, 1, stack) {
+            self.locking += dt;
+        }
+    }
+
+    /// Three occupied corners around the center make a T-spin, it is a full one when both corners
+    /// the T points at are occupied or the piece got there with the last kick (TST and fin kicks).
+    /// The 180 degree rotations count the same as the other ones
+    pub fn t_spin(&self, stack: &Stack) -> TSpin {
+        let rotated = match self.last_movement {
+            Movement::Rotate | Movement::Rotate180 => true,
+            Movement::None | Movement::Shift => false,
+        };
+
+        if self.shape.shape_type != ShapeType::T || !rotated {
+            return TSpin::None;
+        }
+
+        let grid = &self.grid().grid;
+        let block = |x: i32, y: i32| {
+            (0..4).contains(&x) && (0..4).contains(&y) && grid[y as usize][x as usize] != 0
+        };
+        let sides = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+
+        // Center has three neighbours, the T points at the side without the opposite one,
+        // the rotation systems don't agree on where they are in the grid
+        let (x, y) = match (0..16).map(|i| (i % 4, i / 4)).find(|&(x, y)| {
+            block(x, y)
+                && sides
+                    .iter()
+                    .filter(|(dx, dy)| block(x + dx, y + dy))
+                    .count()
+                    == 3
+        }) {
+            Some(center) => center,
+            None => return TSpin::None,
+        };
+
+        let (dx, dy) = *sides
+            .iter()
+            .find(|(dx, dy)| block(x + dx, y + dy) && !block(x - dx, y - dy))
+            .unwrap();
+
+        // Walls and the floor count as occupied
+        let occupied = |x: i32, y: i32| {
+            let (x, y) = (self.x + x, self.y + y);
+            x < 0
+                || x >= stack.width
+                || y >= stack.height + stack.vanish
+                || (y >= 0 && stack.grid()[y as usize][x as usize] != 0)
+        };
+
+        let corners = [(-1, -1), (1, -1), (1, 1), (-1, 1)]
+            .iter()
+            .filter(|(cx, cy)| occupied(x + cx, y + cy))
+            .count();
+
+        if corners < 3 {
+            return TSpin::None;
+        }
+
+        let front = occupied(x + dx - dy, y + dy - dx) && occupied(x + dx + dy, y + dy + dx);
+        // Kicks of the 180 degree rotations have a different order
+        let last_kick = self.kick == Some(3) && self.last_movement == Movement::Rotate;
+        if front || last_kick {
+            TSpin::Full
+        } else {
+            TSpin::Mini
+        }
+    }
+
+    pub fn reset(&mut self, stack: &Stack) {
+        self.x = (stack.width as f32 / 2.0 - self.shape.grids[0].width as f32 / 2.0) as i32;
+        self.y = stack.vanish - self.shape.grids[0].height - self.shape.grids[0].offset_y;
+        self.rotation = 0;
+        self.last_movement = Movement::None;
+        self.kick = None;
+        self.resets = 0;
+        self.lowest = self.y;
+        self.clear_locking();
+    }
+
+    pub fn shift(&mut self, x: i32, y: i32, stack: &Stack) -> bool {
+        let resting = self.collision(0, 1, stack);
+        if !self.offset(x, y, stack) {
+            return false;
+        }
+
+        self.last_movement = Movement::Shift;
+        self.moved(resting);
+        true
+    }
+
+    fn offset(&mut self, x: i32, y: i32, stack: &Stack) -> bool {
+        if self.collision(x, y, stack) {
+            return false;
+        }
+
+        self.x += x;
+        self.y += y;
+        true
+    }
+
+    /// Restarts the lock delay after a successful move if the policy allows it
+    fn moved(&mut self, resting: bool) {
+        if self.y > self.lowest {
+            self.lowest = self.y;
+            self.resets = 0;
+
+            if self.lock_reset != LockReset::None {
+                self.clear_locking();
+            }
+            return;
+        }
+
+        match self.lock_reset {
+            LockReset::Infinite => self.clear_locking(),
+            LockReset::Move if self.resets < MOVE_RESETS => {
+                if resting {
+                    self.resets += 1;
+                }
+                self.clear_locking();
+            }
+            _ => (),
+        }
+    }
+
+    /// Lock delay resets left with the move reset policy
+    pub fn resets_left(&self) -> Option<u32> {
+        if self.lock_reset == LockReset::Move {
+            Some(MOVE_RESETS - self.resets)
+        } else {
+            None
+        }
+    }
+
+    pub fn rotate(&mut self, clockwise: bool, stack: &Stack) -> bool {
+        let kicks = self.shape.kicks[self.rotation];
+
+        if clockwise {
+            self.turn((self.rotation + 1) % 4, kicks.0, Movement::Rotate, stack)
+        } else {
+            self.turn((self.rotation + 3) % 4, kicks.1, Movement::Rotate, stack)
+        }
+    }
+
+    pub fn rotate_180(&mut self, stack: &Stack) -> bool {
+        let kicks = self.shape.kicks_180[self.rotation];
+        self.turn((self.rotation + 2) % 4, kicks, Movement::Rotate180, stack)
+    }
+
+    fn turn(&mut self, rotation: usize, kicks: Kick, movement: Movement, stack: &Stack) -> bool {
+        if self.shape() == ShapeType::O {
+            return false;
+        }
+
+        let resting = self.collision(0, 1, stack);
+        let last_rotation = self.rotation;
+        let mut rotated = false;
+        let mut kick_used = None;
+
+        self.rotation = rotation;
+
+        if !stack.collision(self) {
+            rotated = true;
+        } else if !self.center_column_blocked(stack) {
+            for (i, kick) in kicks.iter().enumerate() {
+                if self.offset(kick.0, kick.1, stack) {
+                    rotated = true;
+                    kick_used = Some(i);
+                    break;
+                }
+            }
+        }
+
+        if rotated {
+            self.last_movement = movement;
+            self.kick = kick_used;
+            self.moved(resting);
+        } else {
+            self.rotation = last_rotation;
+        }
+
+        rotated
+    }
+
+    pub fn fall(&mut self, stack: &Stack) -> i32 {
+        let mut rows = 0;
+        while self.shift(0, 1, stack) {
+            rows += 1;
+        }
+
+        if rows > 0 {
+            self.last_movement = Movement::Shift;
+        }
+
+        rows
+    }
+
+    pub fn clear_locking(&mut self) {
+        self.locking = Duration::new(0, 0);
+    }
+
+    pub fn locking(&self) -> Duration {
+        self.locking
+    }
+
+    pub fn touching_floor(&mut self, stack: &Stack) -> bool {
+        self.collision(0, 1, stack)
+    }
+
+    pub fn grid(&self) -> &ShapeGrid {
+        &self.shape.grids[self.rotation]
+    }
+
+    pub fn shape(&self) -> ShapeType {
+        self.shape.shape_type
+    }
+
+    /// Number of clockwise rotations from the spawn orientation
+    pub fn rotation(&self) -> usize {
+        self.rotation
+    }
+
+    pub fn kick(&self) -> Option<usize> {
+        self.kick
+    }
+
+    /// Rule of ARS for J, L and T, they don't kick when the first blocked cell of the rotated piece,
+    /// reading the rows from the top, is in the middle column
+    fn center_column_blocked(&self, stack: &Stack) -> bool {
+        if self.shape.rotation_system != RotationSystem::Ars
+            || ![ShapeType::J, ShapeType::L, ShapeType::T].contains(&self.shape())
+        {
+            return false;
+        }
+
+        let grid = self.grid();
+        for my in 0..4 {
+            for mx in 0..4 {
+                if grid.grid[my][mx] == 0 {
+                    continue;
+                }
+
+                let x = self.x + mx as i32;
+                let y = self.y + my as i32;
+
+                let blocked = x < 0
+                    || x >= stack.width
+                    || y < 0
+                    || y >= stack.height + stack.vanish
+                    || stack.grid()[y as usize][x as usize] != 0;
+
+                if blocked {
+                    return mx == 1;
+                }
+            }
+        }
+
+        false
+    }
+
+    fn collision(&mut self, x: i32, y: i32, stack: &Stack) -> bool {
+        self.x += x;
+        self.y += y;
+
+        let result = stack.collision(self);
+
+        self.x -= x;
+        self.y -= y;
+        result
+    }
+}
+
+#[test]
+fn t_spin_test() {
+    let mut stack = Stack::new(10, 20, 20);
+    for &(x, y) in &[(3, 39), (5, 39), (3, 37)] {
+        stack.place_random(x, y);
+    }
+
+    // Pointing right above the floor, rotated up into the slot
+    let mut piece = Piece::new(
+        ShapeType::T,
+        RotationSystem::Srs,
+        LockReset::Infinite,
+        &stack,
+    );
+    piece.x = 3;
+    piece.y = 37;
+    piece.rotation = 1;
+    assert!(piece.rotate(false, &stack));
+    assert_eq!(piece.kick(), None);
+    assert_eq!(piece.t_spin(&stack), TSpin::Mini);
+
+    stack.place_random(5, 37);
+    piece.rotation = 1;
+    assert!(piece.rotate(false, &stack));
+    assert_eq!(piece.t_spin(&stack), TSpin::Full);
+
+    // Turned upside down into the same slot
+    piece.rotation = 0;
+    assert!(piece.rotate_180(&stack));
+    assert_eq!(piece.rotation(), 2);
+    assert_eq!(piece.t_spin(&stack), TSpin::Full);
+
+    piece.shift(0, 0, &stack);
+    assert_eq!(piece.t_spin(&stack), TSpin::None);
+}
+
+#[test]
+fn rotation_system_test() {
+    let mut stack = Stack::new(10, 20, 20);
+
+    // Pointing right against the left wall, it needs to move away from it to turn down
+    for &(rotation_system, kicked) in &[(RotationSystem::Ars, true), (RotationSystem::Nrs, false)] {
+        let mut piece = Piece::new(ShapeType::T, rotation_system, LockReset::Infinite, &stack);
+        piece.x = -1;
+        piece.y = 30;
+        piece.rotation = 3;
+        assert!(!stack.collision(&piece));
+        assert_eq!(piece.rotate(true, &stack), kicked);
+    }
+
+    // Spawns pointing down in ARS, so the T-spin corners are checked the other way around
+    for &(x, y) in &[(3, 39), (5, 39), (3, 37)] {
+        stack.place_random(x, y);
+    }
+
+    let mut piece = Piece::new(
+        ShapeType::T,
+        RotationSystem::Ars,
+        LockReset::Infinite,
+        &stack,
+    );
+    piece.x = 3;
+    piece.y = 37;
+    piece.rotation = 3;
+    assert!(piece.rotate(true, &stack));
+    assert_eq!(piece.t_spin(&stack), TSpin::Full);
+}
+
+#[test]
+fn lock_reset_test() {
+    let stack = Stack::new(10, 20, 20);
+
+    for &(lock_reset, cleared) in &[
+        (LockReset::Infinite, true),
+        (LockReset::Move, true),
+        (LockReset::Step, false),
+        (LockReset::None, false),
+    ] {
+        let mut piece = Piece::new(ShapeType::T, RotationSystem::Srs, lock_reset, &stack);
+        piece.fall(&stack);
+        piece.update(Duration::from_millis(100), &stack);
+
+        assert!(piece.shift(1, 0, &stack));
+        assert_eq!(piece.locking() == Duration::new(0, 0), cleared);
+    }
+
+    let mut piece = Piece::new(ShapeType::T, RotationSystem::Srs, LockReset::Move, &stack);
+    piece.fall(&stack);
+    for i in 0..MOVE_RESETS as i32 {
+        piece.update(Duration::from_millis(100), &stack);
+        assert!(piece.shift(1 - i % 2 * 2, 0, &stack));
+    }
+
+    assert_eq!(piece.resets_left(), Some(0));
+    piece.update(Duration::from_millis(100), &stack);
+    assert!(piece.shift(1, 0, &stack));
+    assert_eq!(piece.locking(), Duration::from_millis(100));
+}
@@ -0,0 +1,124 @@
+use std::time::Duration;
+
+use crate::{replay::ReplayData, Engine};
+
+/// Ticks between the saved states of the engine, rewinding replays at most this many ticks
+const SNAPSHOT_INTERVAL: u32 = 5_000;
+
+pub const SPEEDS: [f32; 5] = [0.25, 0.5, 1.0, 2.0, 4.0];
+
+/// Controls an engine playing a replay, the engine itself is owned by the caller,
+/// so it can be drawn and updated the same way as during a normal game
+pub struct Playback {
+    snapshots: Vec<Engine>,
+    length: u32,
+    pub paused: bool,
+    pub speed: f32,
+}
+
+impl Playback {
+    pub fn new(engine: &Engine, replay_data: &ReplayData) -> Playback {
+        Playback {
+            snapshots: vec![engine.clone()],
+            length: replay_data.length(),
+            paused: false,
+            speed: 1.0,
+        }
+    }
+
+    /// Length of the replay in ticks
+    pub fn length(&self) -> u32 {
+        self.length
+    }
+
+    pub fn finished(&self, engine: &Engine) -> bool {
+        engine.game_over() || engine.ticks() >= self.length
+    }
+
+    pub fn update(&mut self, engine: &mut Engine, dt: Duration) {
+        if self.paused || self.finished(engine) {
+            return;
+        }
+
+        engine.update(dt.mul_f32(self.speed));
+        self.snapshot(engine);
+    }
+
+    /// Plays until the next piece is locked and pauses
+    pub fn step(&mut self, engine: &mut Engine) {
+        self.paused = true;
+
+        let pieces = engine.pieces();
+        while engine.pieces() == pieces && !self.finished(engine) {
+            engine.tick();
+            self.snapshot(engine);
+        }
+    }
+
+    /// Moves to the tick, going back restores the closest earlier snapshot and plays from there.
+    /// Events of the skipped ticks are dropped
+    pub fn seek(&mut self, engine: &mut Engine, tick: u32) {
+        let tick = tick.min(self.length);
+
+        let snapshot = self
+            .snapshots
+            .iter()
+            .rev()
+            .find(|s| s.ticks() <= tick)
+            .unwrap_or(&self.snapshots[0]);
+
+        if tick < engine.ticks() || snapshot.ticks() > engine.ticks() {
+            *engine = snapshot.clone();
+        }
+
+        while engine.ticks() < tick && !engine.game_over() {
+            engine.tick();
+            self.snapshot(engine);
+        }
+
+        engine.events();
+    }
+
+    fn snapshot(&mut self, engine: &Engine) {
+        let last = self.snapshots.last().map_or(0, Engine::ticks);
+        if engine.ticks() >= last + SNAPSHOT_INTERVAL {
+            self.snapshots.push(engine.clone());
+        }
+    }
+}
+
+#[test]
+fn playback_test() {
+    use crate::{action::Action, engine::Rules, mode::Marathon};
+
+    let mut engine = Engine::new(&[5; 32], Rules::default(), Box::new(Marathon::default()));
+    for _ in 0..6 {
+        engine.action(Action::HardDrop, false);
+        engine.update(Duration::from_secs(2));
+    }
+
+    let replay_data = engine.replay_data();
+    let mut replay = Engine::from_replay(&replay_data);
+    let mut playback = Playback::new(&replay, &replay_data);
+    assert_eq!(playback.length(), engine.ticks());
+
+    playback.step(&mut replay);
+    assert!(playback.paused);
+    assert_eq!(replay.pieces(), 1);
+
+    playback.seek(&mut replay, 9_000);
+    let score = replay.score().score();
+    let grid = replay.stack().grid().clone();
+
+    playback.seek(&mut replay, 1_000);
+    assert_eq!(replay.ticks(), 1_000);
+
+    playback.seek(&mut replay, 9_000);
+    assert_eq!(replay.score().score(), score);
+    assert_eq!(replay.stack().grid(), &grid);
+
+    playback.paused = false;
+    playback.speed = 4.0;
+    playback.update(&mut replay, Duration::from_millis(100));
+    assert_eq!(replay.ticks(), 9_400);
+}
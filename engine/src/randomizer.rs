@@ -0,0 +1,229 @@
+use std::collections::VecDeque;
+
+use rand::{rngs::StdRng, seq::SliceRandom, Rng};
+use serde::{Deserialize, Serialize};
+
+use crate::shape::{self, ShapeType};
+
+/// Generator of the piece sequence, all randomness comes from the seeded generator of the bag
+pub trait Randomizer {
+    fn next(&mut self, rng: &mut StdRng) -> ShapeType;
+
+    fn box_clone(&self) -> Box<dyn Randomizer>;
+}
+
+impl Clone for Box<dyn Randomizer> {
+    fn clone(&self) -> Box<dyn Randomizer> {
+        self.box_clone()
+    }
+}
+
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, Default, PartialEq)]
+pub enum RandomizerType {
+    #[default]
+    Bag7,
+    Bag14,
+    Random,
+    /// History of the last 4 pieces with rerolls, like in TGM
+    Tgm,
+    /// Rerolls once on a repeated piece, like on the NES
+    Nes,
+    /// 7-bag that never starts with S or Z
+    Classic,
+}
+
+pub const RANDOMIZERS: [RandomizerType; 6] = [
+    RandomizerType::Bag7,
+    RandomizerType::Bag14,
+    RandomizerType::Random,
+    RandomizerType::Tgm,
+    RandomizerType::Nes,
+    RandomizerType::Classic,
+];
+
+impl RandomizerType {
+    pub fn name(self) -> &'static str {
+        match self {
+            RandomizerType::Bag7 => "7-bag",
+            RandomizerType::Bag14 => "14-bag",
+            RandomizerType::Random => "Random",
+            RandomizerType::Tgm => "TGM history",
+            RandomizerType::Nes => "NES",
+            RandomizerType::Classic => "7-bag, no S/Z start",
+        }
+    }
+
+    pub fn create(self) -> Box<dyn Randomizer> {
+        match self {
+            RandomizerType::Bag7 => Box::new(Bags::new(1, false)),
+            RandomizerType::Bag14 => Box::new(Bags::new(2, false)),
+            RandomizerType::Random => Box::new(Random),
+            RandomizerType::Tgm => Box::new(Tgm::default()),
+            RandomizerType::Nes => Box::new(Nes::default()),
+            RandomizerType::Classic => Box::new(Bags::new(1, true)),
+        }
+    }
+}
+
+/// Shuffled bags with every piece the given number of times
+#[derive(Clone)]
+struct Bags {
+    copies: usize,
+    /// First bag is shuffled again until it doesn't start with S or Z
+    no_sz_start: bool,
+    bag: VecDeque<ShapeType>,
+}
+
+impl Bags {
+    fn new(copies: usize, no_sz_start: bool) -> Bags {
+        Bags {
+            copies,
+            no_sz_start,
+            bag: VecDeque::new(),
+        }
+    }
+}
+
+impl Randomizer for Bags {
+    fn next(&mut self, rng: &mut StdRng) -> ShapeType {
+        if self.bag.is_empty() {
+            let mut shapes = shape::all_shape_types().repeat(self.copies);
+            shapes.shuffle(rng);
+
+            while self.no_sz_start && [ShapeType::S, ShapeType::Z].contains(&shapes[0]) {
+                shapes.shuffle(rng);
+            }
+
+            self.no_sz_start = false;
+            self.bag.extend(shapes);
+        }
+
+        self.bag.pop_front().unwrap()
+    }
+
+    fn box_clone(&self) -> Box<dyn Randomizer> {
+        Box::new(self.clone())
+    }
+}
+
+#[derive(Clone)]
+struct Random;
+
+impl Randomizer for Random {
+    fn next(&mut self, rng: &mut StdRng) -> ShapeType {
+        *shape::all_shape_types().choose(rng).unwrap()
+    }
+
+    fn box_clone(&self) -> Box<dyn Randomizer> {
+        Box::new(self.clone())
+    }
+}
+
+/// Rolls of TGM2 before giving up and taking a piece from the history
+const TGM_ROLLS: usize = 6;
+
+#[derive(Clone)]
+struct Tgm {
+    history: VecDeque<ShapeType>,
+    first: bool,
+}
+
+impl Default for Tgm {
+    fn default() -> Tgm {
+        use ShapeType::*;
+
+        Tgm {
+            history: VecDeque::from(vec![Z, S, S, Z]),
+            first: true,
+        }
+    }
+}
+
+impl Randomizer for Tgm {
+    fn next(&mut self, rng: &mut StdRng) -> ShapeType {
+        use ShapeType::*;
+
+        let shape = if self.first {
+            // Never starts with S, Z or O
+            self.first = false;
+            *[I, J, L, T].choose(rng).unwrap()
+        } else {
+            let shapes = shape::all_shape_types();
+            let mut shape = *shapes.choose(rng).unwrap();
+            for _ in 1..TGM_ROLLS {
+                if !self.history.contains(&shape) {
+                    break;
+                }
+                shape = *shapes.choose(rng).unwrap();
+            }
+            shape
+        };
+
+        self.history.pop_front();
+        self.history.push_back(shape);
+        shape
+    }
+
+    fn box_clone(&self) -> Box<dyn Randomizer> {
+        Box::new(self.clone())
+    }
+}
+
+#[derive(Clone, Default)]
+struct Nes {
+    last: Option<ShapeType>,
+}
+
+impl Randomizer for Nes {
+    fn next(&mut self, rng: &mut StdRng) -> ShapeType {
+        let shapes = shape::all_shape_types();
+
+        // The eighth roll is a dummy one that forces the reroll as well
+        let roll = rng.gen_range(0, shapes.len() + 1);
+        let shape = match shapes.get(roll) {
+            Some(&shape) if Some(shape) != self.last => shape,
+            _ => *shapes.choose(rng).unwrap(),
+        };
+
+        self.last = Some(shape);
+        shape
+    }
+
+    fn box_clone(&self) -> Box<dyn Randomizer> {
+        Box::new(self.clone())
+    }
+}
+
+#[test]
+fn randomizer_test() {
+    use rand::SeedableRng;
+
+    for &randomizer_type in RANDOMIZERS.iter() {
+        let mut rng = StdRng::from_seed([5; 32]);
+        let mut randomizer = randomizer_type.create();
+        let sequence: Vec<ShapeType> = (0..28).map(|_| randomizer.next(&mut rng)).collect();
+
+        let mut rng = StdRng::from_seed([5; 32]);
+        let mut randomizer = randomizer_type.create();
+        let again: Vec<ShapeType> = (0..28).map(|_| randomizer.next(&mut rng)).collect();
+
+        assert_eq!(sequence, again, "{}", randomizer_type.name());
+    }
+
+    for seed in 0..20 {
+        let mut rng = StdRng::from_seed([seed; 32]);
+        let first = RandomizerType::Classic.create().next(&mut rng);
+        assert!(first != ShapeType::S && first != ShapeType::Z);
+
+        let mut rng = StdRng::from_seed([seed; 32]);
+        let first = RandomizerType::Tgm.create().next(&mut rng);
+        assert!(![ShapeType::S, ShapeType::Z, ShapeType::O].contains(&first));
+    }
+
+    let mut rng = StdRng::from_seed([0; 32]);
+    let mut randomizer = RandomizerType::Bag14.create();
+    let bag: Vec<ShapeType> = (0..14).map(|_| randomizer.next(&mut rng)).collect();
+    for shape in shape::all_shape_types() {
+        assert_eq!(bag.iter().filter(|&&s| s == shape).count(), 2);
+    }
+}
@@ -0,0 +1,455 @@
+use std::{
+    collections::VecDeque,
+    ffi::OsStr,
+    fmt, fs,
+    io::{self, Read, Write},
+    path::Path,
+    time::Duration,
+};
+
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    action::Action,
+    engine::{Rules, TICK},
+    export,
+    mode::{self, CommonSettings, ModeSettings},
+    piece::LockReset,
+    score::ScoreTable,
+};
+
+/// First bytes of every replay file, older files don't have them
+pub const MAGIC: &[u8; 8] = b"KLOCKIRP";
+
+/// Version of the replay format, bump it on every change of `ReplayData` or `Action`
+/// and add a way to upgrade the previous version in `ReplayData::decode`.
+/// New settings don't need it, give them a `#[serde(default)]` matching the older games instead
+pub const VERSION: u16 = 1;
+
+/// Action of the player together with the engine tick it was processed on
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq)]
+pub struct TimedAction {
+    pub tick: u32,
+    pub action: Action,
+}
+
+/// Settings of the player that affect the game, delays are in milliseconds
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ReplaySettings {
+    pub entry_delay: u32,
+    pub lock_delay: u32,
+    pub clear_delay: u32,
+    pub das: u32,
+    pub arr: u32,
+    pub mode: ModeSettings,
+    #[serde(default)]
+    pub lock_reset: LockReset,
+    #[serde(default = "default_soft_drop_factor")]
+    pub soft_drop_factor: u32,
+}
+
+fn default_soft_drop_factor() -> u32 {
+    Rules::default().soft_drop_factor
+}
+
+impl ReplaySettings {
+    pub fn rules(&self) -> Rules {
+        Rules {
+            entry_delay: Duration::from_millis(self.entry_delay.into()),
+            lock_delay: Duration::from_millis(self.lock_delay.into()),
+            clear_delay: Duration::from_millis(self.clear_delay.into()),
+            lock_reset: self.lock_reset,
+            soft_drop_factor: self.soft_drop_factor,
+        }
+    }
+}
+
+impl Default for ReplaySettings {
+    fn default() -> ReplaySettings {
+        ReplaySettings {
+            entry_delay: 0,
+            lock_delay: 500,
+            clear_delay: 250,
+            das: 133,
+            arr: 33,
+            mode: ModeSettings::default(),
+            lock_reset: LockReset::Infinite,
+            soft_drop_factor: default_soft_drop_factor(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct Header {
+    pub game_version: String,
+    pub mode: String,
+    /// Unix timestamp of the end of the game
+    pub date: i64,
+    pub player: String,
+    pub score: i32,
+    pub lines: u32,
+    pub pieces: u32,
+    /// Length of the game in milliseconds
+    pub time: u64,
+    #[serde(with = "self_describing")]
+    pub settings: ReplaySettings,
+}
+
+/// Keeps the settings as JSON inside the binary replays, so that the settings added later
+/// can be missing in the older replays
+mod self_describing {
+    use serde::{
+        de::Error as _, ser::Error as _, Deserialize, Deserializer, Serialize, Serializer,
+    };
+
+    use super::ReplaySettings;
+
+    pub fn serialize<S: Serializer>(
+        settings: &ReplaySettings,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            settings.serialize(serializer)
+        } else {
+            let json = serde_json::to_string(settings).map_err(S::Error::custom)?;
+            serializer.serialize_str(&json)
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<ReplaySettings, D::Error> {
+        if deserializer.is_human_readable() {
+            ReplaySettings::deserialize(deserializer)
+        } else {
+            let json = String::deserialize(deserializer)?;
+            serde_json::from_str(&json).map_err(D::Error::custom)
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ReplayData {
+    pub header: Header,
+    pub seed: [u8; 32],
+    pub actions: VecDeque<TimedAction>,
+    /// Format the replay was decoded from, the older ones are upgraded to the current one
+    #[serde(skip)]
+    pub version: u16,
+}
+
+#[derive(Debug)]
+pub enum ReplayError {
+    Io(io::Error),
+    Decompress(io::Error),
+    Deserialize(bincode::Error),
+    Json(serde_json::Error),
+    InvalidSeed,
+    /// Replay was saved by a newer version of the game
+    UnsupportedVersion(u16),
+    /// JSON replay was exported by an older version of the game, only the binary ones are upgraded
+    OutdatedVersion(u16),
+    /// Playback needs the actions sorted by their ticks
+    UnorderedActions,
+    UnknownMode(String),
+    InvalidBoard,
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayError::Io(e) => write!(f, "unable to read the file: {}", e),
+            ReplayError::Decompress(e) => write!(f, "unable to decompress: {}", e),
+            ReplayError::Deserialize(e) => write!(f, "corrupted replay: {}", e),
+            ReplayError::Json(e) => write!(f, "invalid JSON replay: {}", e),
+            ReplayError::InvalidSeed => write!(f, "seed must be 64 hexadecimal digits"),
+            ReplayError::UnsupportedVersion(version) => write!(
+                f,
+                "replay format {} is newer than the supported {}",
+                version, VERSION
+            ),
+            ReplayError::OutdatedVersion(version) => write!(
+                f,
+                "JSON replay format {} is older than the supported {}, export it again",
+                version, VERSION
+            ),
+            ReplayError::UnorderedActions => write!(f, "actions must be sorted by their ticks"),
+            ReplayError::UnknownMode(mode) => write!(f, "unknown mode {}", mode),
+            ReplayError::InvalidBoard => write!(f, "board size is out of the supported range"),
+        }
+    }
+}
+
+impl ReplayData {
+    pub fn new(seed: &[u8; 32]) -> ReplayData {
+        let mut seed_clone = [0; 32];
+        seed_clone.clone_from_slice(seed);
+
+        ReplayData {
+            header: Header::default(),
+            actions: VecDeque::new(),
+            seed: seed_clone,
+            version: VERSION,
+        }
+    }
+
+    pub fn add(&mut self, tick: u32, action: Action) {
+        self.actions.push_back(TimedAction { tick, action });
+    }
+
+    /// Length of the replay in ticks, older replays don't have the time in the header
+    pub fn length(&self) -> u32 {
+        let time = Duration::from_millis(self.header.time);
+        let last = self.actions.back().map_or(0, |a| a.tick + 1);
+
+        ((time.as_micros() / TICK.as_micros()) as u32).max(last)
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut writer = GzEncoder::new(Vec::new(), Compression::best());
+        let bytes = bincode::serialize(&self).unwrap();
+        writer.write_all(&bytes).unwrap();
+
+        let mut encoded = MAGIC.to_vec();
+        encoded.extend_from_slice(&VERSION.to_le_bytes());
+        encoded.extend(writer.finish().unwrap());
+        encoded
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<ReplayData, ReplayError> {
+        let (version, compressed) = if bytes.starts_with(MAGIC) && bytes.len() >= MAGIC.len() + 2 {
+            let version = u16::from_le_bytes([bytes[MAGIC.len()], bytes[MAGIC.len() + 1]]);
+            (version, &bytes[MAGIC.len() + 2..])
+        } else {
+            (0, bytes)
+        };
+
+        if version > VERSION {
+            return Err(ReplayError::UnsupportedVersion(version));
+        }
+
+        let mut reader = GzDecoder::new(compressed);
+        let mut bytes: Vec<u8> = vec![];
+        reader
+            .read_to_end(&mut bytes)
+            .map_err(ReplayError::Decompress)?;
+
+        let replay_data = match version {
+            0 => legacy::upgrade(&bytes)?,
+            _ => bincode::deserialize(&bytes)
+                .map(|replay_data| ReplayData {
+                    version,
+                    ..replay_data
+                })
+                .map_err(ReplayError::Deserialize)?,
+        };
+
+        replay_data.validate()?;
+        Ok(replay_data)
+    }
+
+    /// Rejects the replays the engine can't play back
+    pub fn validate(&self) -> Result<(), ReplayError> {
+        let settings = &self.header.settings.mode;
+        if mode::create(&self.header.mode, settings).is_none() {
+            return Err(ReplayError::UnknownMode(self.header.mode.clone()));
+        }
+
+        if !settings.common.board.valid() {
+            return Err(ReplayError::InvalidBoard);
+        }
+
+        Ok(())
+    }
+
+    pub fn save(&self, path: &Path) {
+        if let Err(e) = fs::write(path, self.encode()) {
+            log::error!("Unable to save replay: {:?}", e)
+        } else {
+            log::info!("Saved replay in {:?}", path);
+        }
+    }
+
+    /// Reads both the binary replays and the exported JSON ones
+    pub fn load(path: &Path) -> Result<ReplayData, ReplayError> {
+        let bytes = fs::read(path).map_err(ReplayError::Io)?;
+        let replay_data = if path.extension() == Some(OsStr::new("json")) {
+            export::from_json(&bytes)?
+        } else {
+            ReplayData::decode(&bytes)?
+        };
+
+        log::info!("Loaded replay from {:?}", path);
+        Ok(replay_data)
+    }
+}
+
+/// Scoring of the game before the score tables, used for the upgraded replays
+fn legacy_scoring() -> ScoreTable {
+    let mut scoring = ScoreTable::guideline();
+    scoring.name = String::from("Legacy");
+    scoring.t_spin[0] = 0;
+    scoring.level_multiplier = false;
+    // Mini T-spins were scored as the full ones and perfect clears had no bonus
+    scoring.t_spin_mini.copy_from_slice(&scoring.t_spin[..3]);
+    scoring.perfect_clear = [0; 4];
+    scoring
+}
+
+/// Pieces fell once a second during the whole game before the levels
+fn legacy_settings() -> ReplaySettings {
+    ReplaySettings {
+        mode: ModeSettings {
+            lines_per_level: 0,
+            gravity: vec![1.0 / 60.0],
+            common: CommonSettings {
+                scoring: legacy_scoring(),
+                ..CommonSettings::default()
+            },
+            ..ModeSettings::default()
+        },
+        ..ReplaySettings::default()
+    }
+}
+
+/// Replays of 0.1.x, without any header and timed by the wall clock
+mod legacy {
+    use std::{collections::VecDeque, time::Duration};
+
+    use serde::Deserialize;
+
+    use super::{Header, ReplayData, ReplayError};
+    use crate::action::Action;
+
+    #[derive(Deserialize, Copy, Clone)]
+    enum LegacyAction {
+        MoveRight,
+        MoveLeft,
+        MoveDown,
+        RotateClockwise,
+        RotateCounterClockwise,
+        HardDrop,
+        SoftDrop,
+        HoldPiece,
+        FallPiece,
+        LockPiece,
+        GameOver,
+    }
+
+    #[derive(Deserialize)]
+    struct LegacyTimedAction {
+        action: LegacyAction,
+        /// Time since the previous action
+        duration: Duration,
+    }
+
+    #[derive(Deserialize)]
+    struct LegacyReplayData {
+        seed: [u8; 32],
+        actions: VecDeque<LegacyTimedAction>,
+    }
+
+    /// Gravity and locking were recorded as actions back then, the engine does them on its own now,
+    /// so the upgraded replay is only as accurate as the old timing was
+    pub fn upgrade(bytes: &[u8]) -> Result<ReplayData, ReplayError> {
+        let legacy: LegacyReplayData =
+            bincode::deserialize(bytes).map_err(ReplayError::Deserialize)?;
+
+        let mut replay_data = ReplayData::new(&legacy.seed);
+        replay_data.version = 0;
+        replay_data.header = Header {
+            game_version: String::from("0.1.5"),
+            mode: String::from("Marathon"),
+            settings: super::legacy_settings(),
+            ..Header::default()
+        };
+
+        let mut elapsed = Duration::new(0, 0);
+        for timed_action in legacy.actions {
+            elapsed += timed_action.duration;
+
+            let action = match timed_action.action {
+                LegacyAction::MoveRight => Action::MoveRight,
+                LegacyAction::MoveLeft => Action::MoveLeft,
+                LegacyAction::MoveDown => Action::MoveDown,
+                LegacyAction::RotateClockwise => Action::RotateClockwise,
+                LegacyAction::RotateCounterClockwise => Action::RotateCounterClockwise,
+                LegacyAction::HardDrop => Action::HardDrop,
+                LegacyAction::SoftDrop => Action::SonicDrop,
+                LegacyAction::HoldPiece => Action::HoldPiece,
+                LegacyAction::GameOver => Action::GameOver,
+                LegacyAction::FallPiece | LegacyAction::LockPiece => continue,
+            };
+
+            replay_data.add(elapsed.as_millis() as u32, action);
+        }
+
+        Ok(replay_data)
+    }
+}
+
+#[test]
+fn replay_test() {
+    use serde::Serialize;
+    use std::time::Duration;
+
+    let mut replay_data = ReplayData::new(&[3; 32]);
+    replay_data.header.player = String::from("player");
+    replay_data.header.mode = String::from("Marathon");
+    replay_data.add(10, Action::HardDrop);
+
+    let decoded = ReplayData::decode(&replay_data.encode()).unwrap();
+    assert_eq!(decoded.header, replay_data.header);
+    assert_eq!(decoded.actions, replay_data.actions);
+    assert_eq!(decoded.version, VERSION);
+
+    #[derive(Serialize)]
+    struct Legacy {
+        seed: [u8; 32],
+        actions: Vec<(u32, Duration)>,
+    }
+
+    // HardDrop and FallPiece in the old numbering
+    let legacy = Legacy {
+        seed: [3; 32],
+        actions: vec![
+            (5, Duration::from_millis(20)),
+            (8, Duration::from_millis(30)),
+        ],
+    };
+
+    let mut writer = GzEncoder::new(Vec::new(), Compression::best());
+    writer
+        .write_all(&bincode::serialize(&legacy).unwrap())
+        .unwrap();
+
+    let upgraded = ReplayData::decode(&writer.finish().unwrap()).unwrap();
+    assert_eq!(upgraded.seed, [3; 32]);
+    assert_eq!(upgraded.version, 0);
+    assert_eq!(
+        upgraded.actions,
+        vec![TimedAction {
+            tick: 20,
+            action: Action::HardDrop
+        }]
+    );
+
+    let mut newer = MAGIC.to_vec();
+    newer.extend_from_slice(&(VERSION + 1).to_le_bytes());
+    assert!(ReplayData::decode(&newer).is_err());
+
+    let mut unknown = replay_data.clone();
+    unknown.header.mode = String::from("Zen");
+    match ReplayData::decode(&unknown.encode()) {
+        Err(ReplayError::UnknownMode(mode)) => assert_eq!(mode, "Zen"),
+        _ => panic!("replay of an unknown mode was decoded"),
+    }
+
+    let mut narrow = replay_data.clone();
+    narrow.header.settings.mode.common.board.width = 0;
+    match ReplayData::decode(&narrow.encode()) {
+        Err(ReplayError::InvalidBoard) => (),
+        _ => panic!("replay with an invalid board was decoded"),
+    }
+}
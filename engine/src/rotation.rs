@@ -0,0 +1,197 @@
+use serde::{Deserialize, Serialize};
+
+use crate::shape::{Kick, Kicks, ShapeGrid, ShapeType};
+
+/// Orientations of the pieces and the kicks tried when a rotation is blocked
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, Default, PartialEq)]
+pub enum RotationSystem {
+    /// Super Rotation System of the guideline
+    #[default]
+    Srs,
+    /// SRS with the symmetric I kicks of TETR.IO
+    SrsPlus,
+    /// Arika Rotation System of TGM, the pieces rest on the bottom of their box
+    /// and kick one column to the side
+    Ars,
+    /// Nintendo Rotation System of the NES, without any kicks
+    Nrs,
+}
+
+pub const ROTATION_SYSTEMS: [RotationSystem; 4] = [
+    RotationSystem::Srs,
+    RotationSystem::SrsPlus,
+    RotationSystem::Ars,
+    RotationSystem::Nrs,
+];
+
+const KICKS_I_PLUS: Kicks = [
+    (
+        &[(1, 0), (-2, 0), (-2, 1), (1, -2)],
+        &[(-1, 0), (2, 0), (2, 1), (-1, -2)],
+    ),
+    (
+        &[(-1, 0), (2, 0), (-1, -2), (2, 1)],
+        &[(-1, 0), (2, 0), (-1, 2), (2, -1)],
+    ),
+    (
+        &[(2, 0), (-1, 0), (2, -1), (-1, 2)],
+        &[(-2, 0), (1, 0), (-2, -1), (1, 2)],
+    ),
+    (
+        &[(1, 0), (-2, 0), (1, -2), (-2, 1)],
+        &[(1, 0), (-2, 0), (1, 2), (-2, -1)],
+    ),
+];
+
+const KICKS_ARS: Kicks = [(&[(1, 0), (-1, 0)], &[(1, 0), (-1, 0)]); 4];
+
+const NO_KICKS: Kicks = [(&[], &[]); 4];
+
+type Grids = [[[usize; 4]; 4]; 4];
+
+const ARS_I: Grids = [
+    [[0, 0, 0, 0], [1, 1, 1, 1], [0, 0, 0, 0], [0, 0, 0, 0]],
+    [[0, 0, 1, 0], [0, 0, 1, 0], [0, 0, 1, 0], [0, 0, 1, 0]],
+    [[0, 0, 0, 0], [1, 1, 1, 1], [0, 0, 0, 0], [0, 0, 0, 0]],
+    [[0, 0, 1, 0], [0, 0, 1, 0], [0, 0, 1, 0], [0, 0, 1, 0]],
+];
+
+const ARS_J: Grids = [
+    [[0, 0, 0, 0], [1, 1, 1, 0], [0, 0, 1, 0], [0, 0, 0, 0]],
+    [[0, 1, 0, 0], [0, 1, 0, 0], [1, 1, 0, 0], [0, 0, 0, 0]],
+    [[0, 0, 0, 0], [1, 0, 0, 0], [1, 1, 1, 0], [0, 0, 0, 0]],
+    [[0, 1, 1, 0], [0, 1, 0, 0], [0, 1, 0, 0], [0, 0, 0, 0]],
+];
+
+const ARS_L: Grids = [
+    [[0, 0, 0, 0], [1, 1, 1, 0], [1, 0, 0, 0], [0, 0, 0, 0]],
+    [[1, 1, 0, 0], [0, 1, 0, 0], [0, 1, 0, 0], [0, 0, 0, 0]],
+    [[0, 0, 0, 0], [0, 0, 1, 0], [1, 1, 1, 0], [0, 0, 0, 0]],
+    [[0, 1, 0, 0], [0, 1, 0, 0], [0, 1, 1, 0], [0, 0, 0, 0]],
+];
+
+const ARS_S: Grids = [
+    [[0, 0, 0, 0], [0, 1, 1, 0], [1, 1, 0, 0], [0, 0, 0, 0]],
+    [[1, 0, 0, 0], [1, 1, 0, 0], [0, 1, 0, 0], [0, 0, 0, 0]],
+    [[0, 0, 0, 0], [0, 1, 1, 0], [1, 1, 0, 0], [0, 0, 0, 0]],
+    [[1, 0, 0, 0], [1, 1, 0, 0], [0, 1, 0, 0], [0, 0, 0, 0]],
+];
+
+const ARS_T: Grids = [
+    [[0, 0, 0, 0], [1, 1, 1, 0], [0, 1, 0, 0], [0, 0, 0, 0]],
+    [[0, 1, 0, 0], [1, 1, 0, 0], [0, 1, 0, 0], [0, 0, 0, 0]],
+    [[0, 0, 0, 0], [0, 1, 0, 0], [1, 1, 1, 0], [0, 0, 0, 0]],
+    [[0, 1, 0, 0], [0, 1, 1, 0], [0, 1, 0, 0], [0, 0, 0, 0]],
+];
+
+const ARS_Z: Grids = [
+    [[0, 0, 0, 0], [1, 1, 0, 0], [0, 1, 1, 0], [0, 0, 0, 0]],
+    [[0, 0, 1, 0], [0, 1, 1, 0], [0, 1, 0, 0], [0, 0, 0, 0]],
+    [[0, 0, 0, 0], [1, 1, 0, 0], [0, 1, 1, 0], [0, 0, 0, 0]],
+    [[0, 0, 1, 0], [0, 1, 1, 0], [0, 1, 0, 0], [0, 0, 0, 0]],
+];
+
+const NRS_I: Grids = [
+    [[0, 0, 0, 0], [0, 0, 0, 0], [1, 1, 1, 1], [0, 0, 0, 0]],
+    [[0, 0, 1, 0], [0, 0, 1, 0], [0, 0, 1, 0], [0, 0, 1, 0]],
+    [[0, 0, 0, 0], [0, 0, 0, 0], [1, 1, 1, 1], [0, 0, 0, 0]],
+    [[0, 0, 1, 0], [0, 0, 1, 0], [0, 0, 1, 0], [0, 0, 1, 0]],
+];
+
+const NRS_J: Grids = [
+    [[0, 0, 0, 0], [1, 1, 1, 0], [0, 0, 1, 0], [0, 0, 0, 0]],
+    [[0, 1, 0, 0], [0, 1, 0, 0], [1, 1, 0, 0], [0, 0, 0, 0]],
+    [[1, 0, 0, 0], [1, 1, 1, 0], [0, 0, 0, 0], [0, 0, 0, 0]],
+    [[0, 1, 1, 0], [0, 1, 0, 0], [0, 1, 0, 0], [0, 0, 0, 0]],
+];
+
+const NRS_L: Grids = [
+    [[0, 0, 0, 0], [1, 1, 1, 0], [1, 0, 0, 0], [0, 0, 0, 0]],
+    [[1, 1, 0, 0], [0, 1, 0, 0], [0, 1, 0, 0], [0, 0, 0, 0]],
+    [[0, 0, 1, 0], [1, 1, 1, 0], [0, 0, 0, 0], [0, 0, 0, 0]],
+    [[0, 1, 0, 0], [0, 1, 0, 0], [0, 1, 1, 0], [0, 0, 0, 0]],
+];
+
+const NRS_S: Grids = [
+    [[0, 0, 0, 0], [0, 1, 1, 0], [1, 1, 0, 0], [0, 0, 0, 0]],
+    [[0, 1, 0, 0], [0, 1, 1, 0], [0, 0, 1, 0], [0, 0, 0, 0]],
+    [[0, 0, 0, 0], [0, 1, 1, 0], [1, 1, 0, 0], [0, 0, 0, 0]],
+    [[0, 1, 0, 0], [0, 1, 1, 0], [0, 0, 1, 0], [0, 0, 0, 0]],
+];
+
+const NRS_T: Grids = [
+    [[0, 0, 0, 0], [1, 1, 1, 0], [0, 1, 0, 0], [0, 0, 0, 0]],
+    [[0, 1, 0, 0], [1, 1, 0, 0], [0, 1, 0, 0], [0, 0, 0, 0]],
+    [[0, 1, 0, 0], [1, 1, 1, 0], [0, 0, 0, 0], [0, 0, 0, 0]],
+    [[0, 1, 0, 0], [0, 1, 1, 0], [0, 1, 0, 0], [0, 0, 0, 0]],
+];
+
+const NRS_Z: Grids = ARS_Z;
+
+impl RotationSystem {
+    pub fn name(self) -> &'static str {
+        match self {
+            RotationSystem::Srs => "SRS",
+            RotationSystem::SrsPlus => "SRS+",
+            RotationSystem::Ars => "ARS",
+            RotationSystem::Nrs => "NRS",
+        }
+    }
+
+    /// Orientations of the piece, `None` for the ones shared with SRS
+    pub fn grids(self, shape_type: ShapeType) -> Option<[ShapeGrid; 4]> {
+        let grids = match (self, shape_type) {
+            (_, ShapeType::O) | (RotationSystem::Srs, _) | (RotationSystem::SrsPlus, _) => {
+                return None
+            }
+            (RotationSystem::Ars, ShapeType::I) => ARS_I,
+            (RotationSystem::Ars, ShapeType::J) => ARS_J,
+            (RotationSystem::Ars, ShapeType::L) => ARS_L,
+            (RotationSystem::Ars, ShapeType::S) => ARS_S,
+            (RotationSystem::Ars, ShapeType::T) => ARS_T,
+            (RotationSystem::Ars, ShapeType::Z) => ARS_Z,
+            (RotationSystem::Nrs, ShapeType::I) => NRS_I,
+            (RotationSystem::Nrs, ShapeType::J) => NRS_J,
+            (RotationSystem::Nrs, ShapeType::L) => NRS_L,
+            (RotationSystem::Nrs, ShapeType::S) => NRS_S,
+            (RotationSystem::Nrs, ShapeType::T) => NRS_T,
+            (RotationSystem::Nrs, ShapeType::Z) => NRS_Z,
+        };
+
+        let block = shape_type as usize;
+        let fit = |grid: &[[usize; 4]; 4]| {
+            let mut grid = *grid;
+            for cell in grid.iter_mut().flat_map(|row| row.iter_mut()) {
+                *cell *= block;
+            }
+            ShapeGrid::fit(grid)
+        };
+
+        Some([
+            fit(&grids[0]),
+            fit(&grids[1]),
+            fit(&grids[2]),
+            fit(&grids[3]),
+        ])
+    }
+
+    /// Kicks of the piece, `None` for the ones shared with SRS
+    pub fn kicks(self, shape_type: ShapeType) -> Option<Kicks> {
+        match (self, shape_type) {
+            (RotationSystem::SrsPlus, ShapeType::I) => Some(KICKS_I_PLUS),
+            // The I piece of TGM never kicks
+            (RotationSystem::Ars, ShapeType::I) | (RotationSystem::Nrs, _) => Some(NO_KICKS),
+            (RotationSystem::Ars, _) => Some(KICKS_ARS),
+            _ => None,
+        }
+    }
+
+    /// Kicks of the 180 degree rotations, `None` for the ones shared with SRS.
+    /// The classic systems never had them, so they turn only in place
+    pub fn kicks_180(self) -> Option<[Kick; 4]> {
+        match self {
+            RotationSystem::Srs | RotationSystem::SrsPlus => None,
+            RotationSystem::Ars | RotationSystem::Nrs => Some([&[]; 4]),
+        }
+    }
+}
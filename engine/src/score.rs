@@ -0,0 +1,225 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    garbage::{COMBO_TABLE, PERFECT_CLEAR_GARBAGE},
+    piece::TSpin,
+};
+
+/// Points of a scoring system, everything except the drops is multiplied
+/// by the level when `level_multiplier` is set
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ScoreTable {
+    pub name: String,
+    /// Clearing one to four rows
+    pub clear: [i32; 4],
+    /// T-spins clearing zero to three rows
+    pub t_spin: [i32; 4],
+    /// Mini T-spins clearing zero to two rows
+    #[serde(default = "guideline_t_spin_mini")]
+    pub t_spin_mini: [i32; 3],
+    /// Bonus for emptying the stack with one to four rows, added after the back-to-back bonus
+    #[serde(default)]
+    pub perfect_clear: [i32; 4],
+    /// Percentage of the points for back-to-back tetrises and T-spins, 100 disables the bonus
+    pub back_to_back: i32,
+    /// Points for every step of the combo
+    pub combo: i32,
+    /// Points per row
+    pub soft_drop: i32,
+    /// Points per row
+    pub hard_drop: i32,
+    pub level_multiplier: bool,
+}
+
+fn guideline_t_spin_mini() -> [i32; 3] {
+    [100, 200, 400]
+}
+
+impl ScoreTable {
+    pub fn guideline() -> ScoreTable {
+        ScoreTable {
+            name: String::from("Guideline"),
+            clear: [100, 300, 500, 800],
+            t_spin: [400, 800, 1200, 1600],
+            t_spin_mini: guideline_t_spin_mini(),
+            perfect_clear: [800, 1200, 1800, 2000],
+            back_to_back: 150,
+            combo: 50,
+            soft_drop: 1,
+            hard_drop: 2,
+            level_multiplier: true,
+        }
+    }
+
+    /// Level 1 here is level 0 of the NES, so the level is the original (level + 1) multiplier
+    pub fn nes() -> ScoreTable {
+        ScoreTable {
+            name: String::from("NES"),
+            clear: [40, 100, 300, 1200],
+            t_spin: [0, 40, 100, 300],
+            t_spin_mini: [0, 40, 100],
+            perfect_clear: [0; 4],
+            back_to_back: 100,
+            combo: 0,
+            soft_drop: 1,
+            hard_drop: 0,
+            level_multiplier: true,
+        }
+    }
+
+    pub fn built_in() -> Vec<ScoreTable> {
+        vec![ScoreTable::guideline(), ScoreTable::nes()]
+    }
+}
+
+impl Default for ScoreTable {
+    fn default() -> ScoreTable {
+        ScoreTable::guideline()
+    }
+}
+
+#[derive(Clone)]
+pub struct Score {
+    table: ScoreTable,
+    score: i32,
+    /// Last clear was a tetris or a T-spin, mini ones included
+    last_hard: bool,
+    combo: Option<i32>,
+    btb: bool,
+}
+
+impl Score {
+    pub fn new(table: ScoreTable) -> Score {
+        Score {
+            table,
+            score: 0,
+            last_hard: false,
+            combo: None,
+            btb: false,
+        }
+    }
+
+    pub fn table(&self) -> &ScoreTable {
+        &self.table
+    }
+
+    pub fn score(&self) -> i32 {
+        self.score
+    }
+
+    pub fn soft_drop(&mut self, rows: i32) {
+        self.score += rows * self.table.soft_drop;
+    }
+
+    pub fn hard_drop(&mut self, rows: i32) {
+        self.score += rows * self.table.hard_drop;
+    }
+
+    pub fn btb(&self) -> bool {
+        self.btb
+    }
+
+    pub fn combo(&self) -> Option<i32> {
+        self.combo
+    }
+
+    /// Scores the locked piece, returns the number of garbage lines to send
+    pub fn lock(&mut self, rows: i32, t_spin: TSpin, perfect_clear: bool, level: u32) -> i32 {
+        let multiplier = if self.table.level_multiplier {
+            level.max(1) as i32
+        } else {
+            1
+        };
+
+        if rows <= 0 {
+            self.combo = None;
+
+            self.score += match t_spin {
+                TSpin::Full => self.table.t_spin[0],
+                TSpin::Mini => self.table.t_spin_mini[0],
+                TSpin::None => 0,
+            } * multiplier;
+
+            return 0;
+        }
+
+        let rows = rows.min(4);
+        let mut score = match t_spin {
+            TSpin::Full if rows < 4 => self.table.t_spin[rows as usize],
+            TSpin::Mini if rows < 3 => self.table.t_spin_mini[rows as usize],
+            _ => self.table.clear[rows as usize - 1],
+        };
+
+        let mut garbage = match (rows, t_spin) {
+            (4, _) => 4,
+            (_, TSpin::Full) => rows * 2,
+            (_, TSpin::Mini) => rows - 1,
+            (3, _) => 3,
+            (2, _) => 1,
+            _ => 0,
+        };
+
+        let hard = rows == 4 || t_spin != TSpin::None;
+
+        self.btb = self.last_hard && hard;
+        if self.btb {
+            score = score * self.table.back_to_back / 100;
+            garbage += 1;
+        }
+        self.last_hard = hard;
+
+        if perfect_clear {
+            score += self.table.perfect_clear[rows as usize - 1];
+            garbage += PERFECT_CLEAR_GARBAGE;
+        }
+
+        if let Some(combo) = &mut self.combo {
+            *combo += 1;
+            score += self.table.combo * *combo;
+
+            let index = (*combo as usize).min(COMBO_TABLE.len() - 1);
+            garbage += COMBO_TABLE[index];
+        } else {
+            self.combo = Some(0);
+        }
+
+        self.score += score * multiplier;
+
+        garbage
+    }
+}
+
+#[test]
+fn score_test() {
+    let mut score = Score::new(ScoreTable::guideline());
+    score.lock(4, TSpin::None, false, 2);
+    assert_eq!(score.score(), 1600);
+    assert!(!score.btb());
+
+    // Back-to-back tetris with the first step of the combo
+    assert_eq!(score.lock(4, TSpin::None, false, 2), 5);
+    assert_eq!(score.score(), 1600 + (1200 + 50) * 2);
+    assert!(score.btb());
+
+    score.lock(0, TSpin::None, false, 2);
+    assert_eq!(score.combo(), None);
+
+    let mut score = Score::new(ScoreTable::nes());
+    for &(rows, points) in &[(1, 40), (2, 100), (3, 300), (4, 1200)] {
+        let before = score.score();
+        score.lock(rows, TSpin::None, false, 10);
+        score.lock(0, TSpin::None, false, 10);
+        assert_eq!(score.score() - before, points * 10);
+    }
+
+    let mut score = Score::new(ScoreTable::guideline());
+    assert_eq!(score.lock(1, TSpin::Mini, false, 1), 0);
+    assert_eq!(score.score(), 200);
+    assert_eq!(score.lock(2, TSpin::Full, false, 1), 5);
+    assert_eq!(score.score(), 200 + 1200 * 3 / 2 + 50);
+    assert!(score.btb());
+
+    let mut score = Score::new(ScoreTable::guideline());
+    assert_eq!(score.lock(4, TSpin::None, true, 1), 14);
+    assert_eq!(score.score(), 800 + 2000);
+}
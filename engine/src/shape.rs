@@ -1,48 +1,57 @@
-use ggez::{nalgebra::Point2, Context, GameResult};
+use serde::{Deserialize, Serialize};
 
-use crate::blocks::Blocks;
+use crate::rotation::RotationSystem;
 
-pub type Kick = [(i32, i32); 4];
+/// Offsets tried in order when the rotated piece doesn't fit
+pub type Kick = &'static [(i32, i32)];
 pub type Kicks = [(Kick, Kick); 4];
 
 const KICKS_JLSTZ: Kicks = [
     (
-        [(-1, 0), (-1, -1), (0, 2), (-1, 2)],
-        [(1, 0), (1, -1), (0, 2), (1, 2)],
+        &[(-1, 0), (-1, -1), (0, 2), (-1, 2)],
+        &[(1, 0), (1, -1), (0, 2), (1, 2)],
     ),
     (
-        [(1, 0), (1, 1), (0, -2), (1, -2)],
-        [(1, 0), (1, 1), (0, -2), (1, -2)],
+        &[(1, 0), (1, 1), (0, -2), (1, -2)],
+        &[(1, 0), (1, 1), (0, -2), (1, -2)],
     ),
     (
-        [(1, 0), (1, -1), (0, 2), (1, 2)],
-        [(-1, 0), (-1, -1), (0, 2), (-1, 2)],
+        &[(1, 0), (1, -1), (0, 2), (1, 2)],
+        &[(-1, 0), (-1, -1), (0, 2), (-1, 2)],
     ),
     (
-        [(-1, 0), (-1, 1), (0, -2), (-1, -2)],
-        [(-1, 0), (-1, 1), (0, -2), (-1, -2)],
+        &[(-1, 0), (-1, 1), (0, -2), (-1, -2)],
+        &[(-1, 0), (-1, 1), (0, -2), (-1, -2)],
     ),
 ];
 
 const KICKS_I: Kicks = [
     (
-        [(-2, 0), (1, 0), (-2, 1), (1, -2)],
-        [(-1, 0), (2, 0), (-1, -2), (2, 1)],
+        &[(-2, 0), (1, 0), (-2, 1), (1, -2)],
+        &[(-1, 0), (2, 0), (-1, -2), (2, 1)],
     ),
     (
-        [(-1, 0), (2, 0), (-1, -2), (2, 1)],
-        [(2, 0), (-1, 0), (2, -1), (-1, 2)],
+        &[(-1, 0), (2, 0), (-1, -2), (2, 1)],
+        &[(2, 0), (-1, 0), (2, -1), (-1, 2)],
     ),
     (
-        [(2, 0), (-1, 0), (2, -1), (-1, 2)],
-        [(1, 0), (-2, 0), (1, 2), (-2, -1)],
+        &[(2, 0), (-1, 0), (2, -1), (-1, 2)],
+        &[(1, 0), (-2, 0), (1, 2), (-2, -1)],
     ),
     (
-        [(1, 0), (-2, 0), (1, 2), (-2, -1)],
-        [(-2, 0), (1, 0), (-2, 1), (1, -2)],
+        &[(1, 0), (-2, 0), (1, 2), (-2, -1)],
+        &[(-2, 0), (1, 0), (-2, 1), (1, -2)],
     ),
 ];
 
+/// Kicks of the 180 degree rotations from every orientation, the same for all pieces
+const KICKS_180: [Kick; 4] = [
+    &[(0, -1), (1, -1), (-1, -1), (1, 0), (-1, 0)],
+    &[(1, 0), (1, -2), (1, -1), (0, -2), (0, -1)],
+    &[(0, 1), (-1, 1), (1, 1), (-1, 0), (1, 0)],
+    &[(-1, 0), (-1, -2), (-1, -1), (0, -2), (0, -1)],
+];
+
 #[derive(Clone)]
 pub struct ShapeGrid {
     pub offset_x: i32,
@@ -68,9 +77,29 @@ impl ShapeGrid {
             grid,
         }
     }
+
+    /// Finds the bounding box of the blocks in the grid
+    pub(crate) fn fit(grid: [[usize; 4]; 4]) -> ShapeGrid {
+        let rows: Vec<i32> = (0..4)
+            .filter(|&y| grid[y].iter().any(|&c| c != 0))
+            .map(|y| y as i32)
+            .collect();
+        let columns: Vec<i32> = (0..4)
+            .filter(|&x| grid.iter().any(|row| row[x] != 0))
+            .map(|x| x as i32)
+            .collect();
+
+        ShapeGrid {
+            offset_x: columns[0],
+            offset_y: rows[0],
+            width: columns.len() as i32,
+            height: rows.len() as i32,
+            grid,
+        }
+    }
 }
 
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq)]
 pub enum ShapeType {
     I = 1,
     J,
@@ -89,51 +118,39 @@ pub fn all_shape_types() -> Vec<ShapeType> {
 #[derive(Clone)]
 pub struct Shape {
     pub shape_type: ShapeType,
+    pub rotation_system: RotationSystem,
     pub grids: [ShapeGrid; 4],
     pub kicks: Kicks,
+    pub kicks_180: [Kick; 4],
 }
 
 impl Shape {
-    pub fn draw(
-        &self,
-        ctx: &mut Context,
-        rotation: usize,
-        position: Point2<f32>,
-        blocks: &mut Blocks,
-        block_size: i32,
-        alpha: f32,
-    ) -> GameResult {
-        blocks.clear();
-
-        let grid = &self.grids[rotation];
-
-        for y in 0..4 {
-            for x in 0..4 {
-                let block = grid.grid[y][x];
-                if block == 0 {
-                    continue;
-                }
+    pub fn new(shape_type: ShapeType, rotation_system: RotationSystem) -> Shape {
+        let mut shape = Shape::srs(shape_type);
+        shape.rotation_system = rotation_system;
 
-                let dest = Point2::new(
-                    position[0] + (x as i32 * block_size) as f32,
-                    position[1] + (y as i32 * block_size) as f32,
-                );
+        if let Some(grids) = rotation_system.grids(shape_type) {
+            shape.grids = grids;
+        }
 
-                blocks.add(block, block_size, dest, alpha);
-            }
+        if let Some(kicks) = rotation_system.kicks(shape_type) {
+            shape.kicks = kicks;
         }
 
-        blocks.draw(ctx)?;
+        if let Some(kicks) = rotation_system.kicks_180() {
+            shape.kicks_180 = kicks;
+        }
 
-        Ok(())
+        shape
     }
 
-    pub fn new(shape_type: ShapeType) -> Shape {
+    fn srs(shape_type: ShapeType) -> Shape {
         match shape_type {
             ShapeType::Z => {
                 let x = ShapeType::Z as usize;
                 Shape {
                     shape_type: ShapeType::Z,
+                    rotation_system: RotationSystem::Srs,
                     grids: [
                         ShapeGrid::new(
                             0,
@@ -165,12 +182,14 @@ impl Shape {
                         ),
                     ],
                     kicks: KICKS_JLSTZ,
+                    kicks_180: KICKS_180,
                 }
             }
             ShapeType::L => {
                 let x = ShapeType::L as usize;
                 Shape {
                     shape_type: ShapeType::L,
+                    rotation_system: RotationSystem::Srs,
                     grids: [
                         ShapeGrid::new(
                             0,
@@ -202,12 +221,14 @@ impl Shape {
                         ),
                     ],
                     kicks: KICKS_JLSTZ,
+                    kicks_180: KICKS_180,
                 }
             }
             ShapeType::O => {
                 let x = ShapeType::O as usize;
                 Shape {
                     shape_type: ShapeType::O,
+                    rotation_system: RotationSystem::Srs,
                     grids: [
                         ShapeGrid::new(
                             0,
@@ -239,12 +260,14 @@ impl Shape {
                         ),
                     ],
                     kicks: KICKS_JLSTZ,
+                    kicks_180: KICKS_180,
                 }
             }
             ShapeType::S => {
                 let x = ShapeType::S as usize;
                 Shape {
                     shape_type: ShapeType::S,
+                    rotation_system: RotationSystem::Srs,
                     grids: [
                         ShapeGrid::new(
                             0,
@@ -276,12 +299,14 @@ impl Shape {
                         ),
                     ],
                     kicks: KICKS_JLSTZ,
+                    kicks_180: KICKS_180,
                 }
             }
             ShapeType::I => {
                 let x = ShapeType::I as usize;
                 Shape {
                     shape_type: ShapeType::I,
+                    rotation_system: RotationSystem::Srs,
                     grids: [
                         ShapeGrid::new(
                             0,
@@ -313,12 +338,14 @@ impl Shape {
                         ),
                     ],
                     kicks: KICKS_I,
+                    kicks_180: KICKS_180,
                 }
             }
             ShapeType::J => {
                 let x = ShapeType::J as usize;
                 Shape {
                     shape_type: ShapeType::J,
+                    rotation_system: RotationSystem::Srs,
                     grids: [
                         ShapeGrid::new(
                             0,
@@ -350,12 +377,14 @@ impl Shape {
                         ),
                     ],
                     kicks: KICKS_JLSTZ,
+                    kicks_180: KICKS_180,
                 }
             }
             ShapeType::T => {
                 let x = ShapeType::T as usize;
                 Shape {
                     shape_type: ShapeType::T,
+                    rotation_system: RotationSystem::Srs,
                     grids: [
                         ShapeGrid::new(
                             0,
@@ -387,6 +416,7 @@ impl Shape {
                         ),
                     ],
                     kicks: KICKS_JLSTZ,
+                    kicks_180: KICKS_180,
                 }
             }
         }
@@ -0,0 +1,346 @@
+use std::time::Duration;
+
+use rand::Rng;
+
+use crate::piece::Piece;
+
+#[derive(Clone)]
+struct Clearing {
+    rows: Vec<i32>,
+    current_duration: Duration,
+    max_duration: Duration,
+}
+
+/// Block used for the garbage rows
+pub const GARBAGE_BLOCK: usize = 8;
+
+pub type Row = Vec<usize>;
+pub type Grid = Vec<Row>;
+
+#[derive(Clone)]
+pub struct Stack {
+    pub width: i32,
+    pub height: i32,
+    pub vanish: i32,
+
+    clearing: Option<Clearing>,
+    grid: Grid,
+}
+
+pub enum Locked {
+    Collision,
+    Success(i32),
+}
+
+impl Stack {
+    pub fn new(width: i32, height: i32, vanish: i32) -> Stack {
+        Stack {
+            width,
+            height,
+            vanish,
+            clearing: None,
+            grid: vec![vec![0; width as usize]; (height + vanish) as usize],
+        }
+    }
+
+    pub fn place_random(&mut self, x: usize, y: usize) {
+        self.grid[y][x] = rand::thread_rng().gen_range(1, 8);
+    }
+
+    pub fn grid(&self) -> &Grid {
+        &self.grid
+    }
+
+    pub fn clear(&mut self) {
+        self.grid = vec![vec![0; self.width as usize]; (self.height + self.vanish) as usize]
+    }
+
+    pub fn collision(&self, piece: &Piece) -> bool {
+        let grid = piece.grid();
+        let x = piece.x + grid.offset_x;
+        let y = piece.y + grid.offset_y;
+
+        if x < 0
+            || x + grid.width > self.width
+            || y < 0
+            || y + grid.height > self.height + self.vanish
+        {
+            return true;
+        }
+
+        for my in 0..grid.height {
+            for mx in 0..grid.width {
+                let c = grid.grid[(my + grid.offset_y) as usize][(mx + grid.offset_x) as usize];
+                if c != 0 && self.grid[(y + my) as usize][(x + mx) as usize] != 0 {
+                    return true;
+                }
+            }
+        }
+
+        false
+    }
+
+    pub fn lock(&mut self, piece: &Piece, clear_delay: Duration) -> Locked {
+        let mut collision = self.collision(piece);
+
+        let grid = piece.grid();
+        let x = piece.x + grid.offset_x;
+        let y = piece.y + grid.offset_y;
+
+        if y + grid.height <= self.vanish {
+            collision = true;
+        }
+
+        for my in 0..grid.height {
+            for mx in 0..grid.width {
+                let c = grid.grid[(my + grid.offset_y) as usize][(mx + grid.offset_x) as usize];
+                if c != 0 {
+                    self.grid[(y + my) as usize][(x + mx) as usize] = c;
+                }
+            }
+        }
+
+        if !collision {
+            Locked::Success(self.clear_full_rows(clear_delay))
+        } else {
+            Locked::Collision
+        }
+    }
+
+    /// All blocks are in the full rows, so the stack will be empty once they are cleared
+    pub fn perfect_clear(&self) -> bool {
+        self.grid
+            .iter()
+            .all(|row| row.iter().all(|&c| c != 0) || row.iter().all(|&c| c == 0))
+    }
+
+    pub fn blocked(&self) -> bool {
+        self.clearing.is_some()
+    }
+
+    /// Rows that are being cleared together with the progress of clearing from 0.0 to 1.0
+    pub fn clearing(&self) -> Option<(&[i32], f32)> {
+        self.clearing.as_ref().map(|clearing| {
+            let ratio = if clearing.max_duration > Duration::new(0, 0) {
+                clearing.current_duration.as_secs_f32() / clearing.max_duration.as_secs_f32()
+            } else {
+                1.0
+            };
+
+            (&clearing.rows[..], ratio)
+        })
+    }
+
+    /// Advances clearing of the rows, returns the removed rows with their blocks
+    pub fn update(&mut self, dt: Duration) -> Option<Vec<(i32, Row)>> {
+        let clearing = self.clearing.as_mut()?;
+        clearing.current_duration += dt;
+
+        if clearing.current_duration < clearing.max_duration {
+            return None;
+        }
+
+        let rows = clearing.rows.clone();
+        self.clearing = None;
+
+        let removed = rows
+            .iter()
+            .map(|&y| (y, self.grid[y as usize].clone()))
+            .collect();
+
+        for &y in &rows {
+            for y in (1..=y).rev() {
+                for x in 0..self.width {
+                    self.grid[y as usize][x as usize] = self.grid[y as usize - 1][x as usize];
+                }
+            }
+        }
+
+        Some(removed)
+    }
+
+    fn clear_full_rows(&mut self, clear_delay: Duration) -> i32 {
+        let rows = self.get_full_rows();
+        let length = rows.len();
+
+        if length > 0 {
+            self.clear_rows(&rows, clear_delay);
+        }
+
+        length as i32
+    }
+
+    fn get_full_rows(&self) -> Vec<i32> {
+        let mut rows = vec![];
+
+        for y in 0..self.height + self.vanish {
+            let mut full = true;
+
+            for x in 0..self.width {
+                if self.grid[y as usize][x as usize] == 0 {
+                    full = false;
+                    break;
+                }
+            }
+
+            if full {
+                rows.push(y);
+            }
+        }
+
+        rows
+    }
+
+    fn clear_rows(&mut self, rows: &[i32], clear_delay: Duration) {
+        self.clearing = Some(Clearing {
+            rows: Vec::from(rows),
+            current_duration: Duration::new(0, 0),
+            max_duration: clear_delay,
+        });
+    }
+
+    /// Pushes the stack up and fills the bottom rows with garbage,
+    /// returns false if any blocks were pushed out of the stack
+    pub fn add_garbage(&mut self, lines: usize, hole: usize) -> bool {
+        let lines = lines.min(self.grid.len());
+
+        let overflow = self.grid[..lines]
+            .iter()
+            .any(|row| row.iter().any(|&block| block != 0));
+
+        self.grid.drain(..lines);
+
+        for _ in 0..lines {
+            let mut row = vec![GARBAGE_BLOCK; self.width as usize];
+            if let Some(block) = row.get_mut(hole) {
+                *block = 0;
+            }
+
+            self.grid.push(row);
+        }
+
+        !overflow
+    }
+
+    pub fn game_over(&mut self) {
+        let mut rows = vec![];
+        for y in 0..self.height + self.vanish {
+            for x in 0..self.width {
+                if self.grid[y as usize][x as usize] != 0 {
+                    rows.push(y);
+                    break;
+                }
+            }
+        }
+
+        self.clear_rows(&rows, Duration::new(0, 0));
+    }
+
+    /// Fills all but the top four rows of the stack, leaving the rightmost column empty
+    pub fn debug_tetris(&mut self) {
+        let bottom = (self.vanish + self.height) as usize;
+        let rows = (self.height - 4).max(0) as usize;
+
+        let mut bricks: Vec<(usize, usize)> = vec![];
+        for y in bottom - rows..bottom {
+            for x in 0..self.width as usize - 1 {
+                bricks.push((y, x));
+            }
+        }
+
+        self.clear();
+
+        for (y, x) in bricks {
+            self.place_random(x, y);
+        }
+    }
+
+    /// Tower of T-spin slots on the left, it reaches above smaller boards into the vanish zone
+    pub fn debug_t_spin(&mut self) {
+        let bottom = (self.vanish + self.height - 1) as usize;
+
+        let mut bricks: Vec<(usize, usize)> = [
+            (0, 0),
+            (0, 1),
+            (1, 0),
+            (2, 0),
+            (2, 1),
+            (3, 0),
+            (3, 1),
+            (4, 0),
+            (5, 0),
+            (5, 1),
+            (6, 0),
+            (6, 1),
+            (7, 0),
+            (8, 0),
+            (8, 1),
+            (9, 0),
+            (9, 1),
+            (10, 0),
+            (11, 0),
+            (11, 1),
+            (13, 2),
+            (14, 2),
+        ]
+        .iter()
+        .map(|&(y, x)| (bottom - y, x))
+        .collect();
+
+        for y in 0..14 {
+            bricks.push((bottom - y, 3));
+        }
+
+        for y in 0..12 {
+            for x in 4..self.width as usize {
+                bricks.push((bottom - y, x));
+            }
+        }
+
+        self.clear();
+
+        for (y, x) in bricks {
+            self.place_random(x, y);
+        }
+    }
+}
+
+#[test]
+fn garbage_test() {
+    let mut stack = Stack::new(10, 20, 20);
+    stack.grid[39][0] = 1;
+
+    assert!(stack.add_garbage(2, 3));
+    assert_eq!(stack.grid()[37][0], 1);
+    assert_eq!(stack.grid()[38][3], 0);
+    assert_eq!(stack.grid()[39][4], GARBAGE_BLOCK);
+
+    assert!(!stack.add_garbage(40, 0));
+}
+
+#[test]
+fn perfect_clear_test() {
+    let mut stack = Stack::new(10, 20, 20);
+    for x in 0..10 {
+        stack.grid[39][x] = 1;
+    }
+    assert!(stack.perfect_clear());
+
+    stack.grid[38][0] = 1;
+    assert!(!stack.perfect_clear());
+}
+
+#[test]
+fn debug_test() {
+    for &(width, height) in &[(10, 20), (4, 10), (20, 30)] {
+        let mut stack = Stack::new(width, height, 20);
+
+        stack.debug_tetris();
+        let bottom = stack.grid()[(height + 19) as usize].clone();
+        assert!(bottom[..width as usize - 1].iter().all(|&c| c != 0));
+        assert_eq!(bottom[width as usize - 1], 0);
+
+        stack.debug_t_spin();
+        assert_ne!(stack.grid()[(height + 19) as usize][0], 0);
+    }
+}
@@ -0,0 +1,37 @@
+use crate::piece::TSpin;
+
+/// Counters of the notable clears during the game
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Stats {
+    /// T-spins that cleared at least one row
+    pub t_spins: u32,
+    /// Mini T-spins that cleared at least one row, not included in `t_spins`
+    pub t_spin_minis: u32,
+    pub tetrises: u32,
+    pub max_combo: u32,
+    pub perfect_clears: u32,
+}
+
+impl Stats {
+    pub fn lock(&mut self, rows: i32, t_spin: TSpin, perfect_clear: bool, combo: Option<i32>) {
+        if rows > 0 {
+            match t_spin {
+                TSpin::Full => self.t_spins += 1,
+                TSpin::Mini => self.t_spin_minis += 1,
+                TSpin::None => (),
+            }
+        }
+
+        if rows == 4 {
+            self.tetrises += 1;
+        }
+
+        if perfect_clear {
+            self.perfect_clears += 1;
+        }
+
+        if let Some(combo) = combo {
+            self.max_combo = self.max_combo.max(combo as u32);
+        }
+    }
+}
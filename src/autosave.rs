@@ -0,0 +1,112 @@
+use std::{fs, path::PathBuf, time::SystemTime};
+
+use chrono::NaiveDateTime;
+use klocki_engine::replay::ReplayData;
+
+use crate::{
+    browser,
+    settings::{AutoSave, Replays},
+};
+
+/// Automatically saved replays start with it and end with the timestamp, only those are ever
+/// removed. Renaming a replay in the browser keeps it forever
+const PREFIX: &str = "Auto ";
+
+/// Format of the time the replay was saved at, ending its name
+pub const TIMESTAMP: &str = "%Y%m%d_%H%M%S";
+
+/// Personal bests are saved with it and kept like the manually saved replays
+const BEST_PREFIX: &str = "Best ";
+
+pub fn should_save(settings: &Replays, new_record: bool) -> bool {
+    match settings.auto_save {
+        AutoSave::Off => false,
+        AutoSave::Always | AutoSave::Last => true,
+        AutoSave::PersonalBest => new_record,
+    }
+}
+
+pub fn save(settings: &Replays, replay_data: &ReplayData, name: &str, new_record: bool) {
+    let prefix = if new_record { BEST_PREFIX } else { PREFIX };
+
+    let mut path = browser::replays_dir();
+    path.push(format!("{}{}.klocki", prefix, name));
+    replay_data.save(&path);
+
+    clean_up(settings);
+}
+
+fn auto_saved(file_name: &str) -> bool {
+    let name = match file_name
+        .strip_prefix(PREFIX)
+        .and_then(|name| name.strip_suffix(".klocki"))
+    {
+        Some(name) => name,
+        None => return false,
+    };
+
+    let timestamp = name.rsplit(' ').next().unwrap_or_default();
+    NaiveDateTime::parse_from_str(timestamp, TIMESTAMP).is_ok()
+}
+
+/// Removes the oldest automatically saved replays above the limits
+fn clean_up(settings: &Replays) {
+    let dir = match fs::read_dir(browser::replays_dir()) {
+        Ok(dir) => dir,
+        Err(e) => {
+            log::error!("Unable to list replays: {:?}", e);
+            return;
+        }
+    };
+
+    let mut total = 0;
+    let mut saved: Vec<(SystemTime, u64, PathBuf)> = vec![];
+
+    for entry in dir.filter_map(|e| e.ok()) {
+        let metadata = match entry.metadata() {
+            Ok(metadata) if metadata.is_file() => metadata,
+            _ => continue,
+        };
+
+        total += metadata.len();
+
+        if auto_saved(&entry.file_name().to_string_lossy()) {
+            let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+            saved.push((modified, metadata.len(), entry.path()));
+        }
+    }
+
+    saved.sort();
+
+    let max_size = u64::from(settings.max_size) * 1024 * 1024;
+    let mut count = saved.len();
+
+    for (_, size, path) in saved {
+        let too_many = settings.auto_save == AutoSave::Last && count > settings.keep_last as usize;
+        if !too_many && total <= max_size {
+            break;
+        }
+
+        match fs::remove_file(&path) {
+            Ok(()) => {
+                log::info!("Removed old replay {:?}", path);
+                total -= size;
+                count -= 1;
+            }
+            Err(e) => log::error!("Unable to remove replay {:?}: {:?}", path, e),
+        }
+    }
+
+    if total > max_size {
+        log::warn!("Replays directory is above the size cap, remove some of the saved replays");
+    }
+}
+
+#[test]
+fn auto_saved_test() {
+    assert!(auto_saved("Auto Score 1200 - 20200314_152653.klocki"));
+    assert!(!auto_saved("Auto Score 1200 - 20200314_152653.json"));
+    assert!(!auto_saved("Best Score 1200 - 20200314_152653.klocki"));
+    assert!(!auto_saved("Automatic.klocki"));
+    assert!(!auto_saved("Auto Score 1200 - my best.klocki"));
+}
@@ -3,6 +3,7 @@ use ggez::{
     nalgebra::{Point2, Vector2},
     Context, GameResult,
 };
+use klocki_engine::shape::ShapeGrid;
 
 pub const BLOCKS_NUM: usize = 10;
 
@@ -78,6 +79,35 @@ impl Blocks {
         }
     }
 
+    pub fn draw_shape(
+        &mut self,
+        ctx: &mut Context,
+        grid: &ShapeGrid,
+        position: Point2<f32>,
+        block_size: i32,
+        alpha: f32,
+    ) -> GameResult {
+        self.clear();
+
+        for y in 0..4 {
+            for x in 0..4 {
+                let block = grid.grid[y][x];
+                if block == 0 {
+                    continue;
+                }
+
+                let dest = Point2::new(
+                    position[0] + (x as i32 * block_size) as f32,
+                    position[1] + (y as i32 * block_size) as f32,
+                );
+
+                self.add(block, block_size, dest, alpha);
+            }
+        }
+
+        self.draw(ctx)
+    }
+
     pub fn draw(&self, ctx: &mut Context) -> GameResult {
         graphics::draw(ctx, &self.batch, DrawParam::new())?;
 
@@ -0,0 +1,338 @@
+use std::{
+    cmp::Reverse,
+    ffi::{OsStr, OsString},
+    fs,
+    path::PathBuf,
+    time::Duration,
+};
+
+use chrono::{Local, TimeZone};
+use imgui::{im_str, ChildWindow, ImString, Selectable, Ui};
+use klocki_engine::{
+    export,
+    replay::{Header, ReplayData},
+};
+
+use crate::utils;
+
+pub fn replays_dir() -> PathBuf {
+    let mut path = dirs::data_local_dir().unwrap_or_default();
+    path.push("klocki");
+    path.push("replays");
+    path
+}
+
+struct Entry {
+    path: PathBuf,
+    name: String,
+    header: Header,
+}
+
+#[derive(Copy, Clone, PartialEq)]
+enum SortBy {
+    Date,
+    Score,
+    Time,
+    Mode,
+    Name,
+}
+
+const SORT_BY: [(SortBy, &str); 5] = [
+    (SortBy::Date, "Date"),
+    (SortBy::Score, "Score"),
+    (SortBy::Time, "Duration"),
+    (SortBy::Mode, "Mode"),
+    (SortBy::Name, "Name"),
+];
+
+pub enum Choice {
+    Play(PathBuf),
+    /// Play a live game against the replay
+    Race(PathBuf),
+}
+
+/// List of the saved replays
+pub struct Browser {
+    entries: Vec<Entry>,
+    sort_by: SortBy,
+    selected: Option<usize>,
+    name: ImString,
+    /// Delete was clicked and waits for the confirmation
+    deleting: bool,
+}
+
+impl Browser {
+    pub fn new() -> Browser {
+        Browser {
+            entries: vec![],
+            sort_by: SortBy::Date,
+            selected: None,
+            name: ImString::with_capacity(64),
+            deleting: false,
+        }
+    }
+
+    /// Reads the headers of all replays in the replays directory
+    pub fn refresh(&mut self) {
+        self.entries.clear();
+        self.selected = None;
+
+        let dir = match fs::read_dir(replays_dir()) {
+            Ok(dir) => dir,
+            Err(e) => {
+                log::error!("Unable to list replays: {:?}", e);
+                return;
+            }
+        };
+
+        for path in dir.filter_map(|e| e.ok()).map(|e| e.path()) {
+            let extension = path.extension();
+            if extension != Some(OsStr::new("klocki")) && extension != Some(OsStr::new("json")) {
+                continue;
+            }
+
+            match ReplayData::load(&path) {
+                Ok(replay_data) => self.entries.push(Entry {
+                    name: String::from(path.file_stem().unwrap().to_string_lossy()),
+                    path,
+                    header: replay_data.header,
+                }),
+                Err(e) => log::warn!("Skipping replay {:?}: {}", path, e),
+            }
+        }
+
+        self.sort();
+    }
+
+    fn sort(&mut self) {
+        match self.sort_by {
+            SortBy::Date => self.entries.sort_by_key(|e| Reverse(e.header.date)),
+            SortBy::Score => self.entries.sort_by_key(|e| Reverse(e.header.score)),
+            SortBy::Time => self.entries.sort_by_key(|e| e.header.time),
+            SortBy::Mode => self
+                .entries
+                .sort_by(|a, b| a.header.mode.cmp(&b.header.mode)),
+            SortBy::Name => self.entries.sort_by(|a, b| a.name.cmp(&b.name)),
+        }
+    }
+
+    fn select(&mut self, id: usize) {
+        self.selected = Some(id);
+        self.deleting = false;
+        self.name.clear();
+        self.name.push_str(&self.entries[id].name);
+    }
+
+    fn rename(&mut self, id: usize) {
+        let name = self.name.to_str().trim();
+        if name.is_empty() || name.contains(&['/', '\\'][..]) {
+            return;
+        }
+
+        // The name may contain dots, so the extension is appended instead of replaced
+        let entry = &mut self.entries[id];
+        let mut file_name = OsString::from(name);
+        if let Some(extension) = entry.path.extension() {
+            file_name.push(".");
+            file_name.push(extension);
+        }
+        let path = entry.path.with_file_name(file_name);
+
+        if path.exists() {
+            log::warn!("Replay {:?} already exists", path);
+            return;
+        }
+
+        match fs::rename(&entry.path, &path) {
+            Ok(()) => {
+                entry.name = String::from(name);
+                entry.path = path;
+            }
+            Err(e) => log::error!("Unable to rename replay: {:?}", e),
+        }
+    }
+
+    fn json(&self, id: usize) -> bool {
+        self.entries[id].path.extension() == Some(OsStr::new("json"))
+    }
+
+    /// Converts between the binary and the JSON replays, keeping the name
+    fn convert(&mut self, id: usize) {
+        let entry = &self.entries[id];
+        let json = self.json(id);
+
+        let path = entry
+            .path
+            .with_extension(if json { "klocki" } else { "json" });
+        if path.exists() {
+            log::warn!("Replay {:?} already exists", path);
+            return;
+        }
+
+        let replay_data = match ReplayData::load(&entry.path) {
+            Ok(replay_data) => replay_data,
+            Err(e) => {
+                log::error!("Unable to load replay {:?}: {}", entry.path, e);
+                return;
+            }
+        };
+
+        if json {
+            replay_data.save(&path);
+        } else {
+            match fs::write(&path, export::to_json(&replay_data)) {
+                Ok(()) => log::info!("Exported replay to {:?}", path),
+                Err(e) => log::error!("Unable to export replay: {:?}", e),
+            }
+        }
+
+        self.refresh();
+    }
+
+    fn delete(&mut self, id: usize) {
+        self.deleting = false;
+        match fs::remove_file(&self.entries[id].path) {
+            Ok(()) => {
+                log::info!("Deleted replay {:?}", self.entries[id].path);
+                self.entries.remove(id);
+                self.selected = None;
+            }
+            Err(e) => log::error!("Unable to delete replay: {:?}", e),
+        }
+    }
+
+    /// Returns the replay the player chose to watch or race against
+    pub fn draw(&mut self, ui: &Ui) -> Option<Choice> {
+        let mut choice = None;
+
+        ui.text(im_str!("Sort by:"));
+        for &(sort_by, label) in SORT_BY.iter() {
+            ui.same_line(0.0);
+            if ui.radio_button_bool(&ImString::new(label), self.sort_by == sort_by) {
+                self.sort_by = sort_by;
+                self.selected = None;
+                self.sort();
+            }
+        }
+
+        ui.same_line(0.0);
+        if ui.button(im_str!("Refresh"), [0.0, 0.0]) {
+            self.refresh();
+        }
+
+        let mut clicked = None;
+        ChildWindow::new(im_str!("replays"))
+            .size([0.0, -120.0])
+            .border(true)
+            .build(ui, || {
+                ui.columns(5, im_str!("replay_columns"), true);
+                for title in &["Name", "Mode", "Score", "Duration", "Date"] {
+                    ui.text(ImString::new(*title));
+                    ui.next_column();
+                }
+                ui.separator();
+
+                for (id, entry) in self.entries.iter().enumerate() {
+                    let header = &entry.header;
+
+                    if Selectable::new(&im_str!("{}##{}", entry.name, id))
+                        .selected(self.selected == Some(id))
+                        .span_all_columns(true)
+                        .allow_double_click(true)
+                        .build(ui)
+                    {
+                        clicked = Some(id);
+                        if ui.is_mouse_double_clicked(imgui::MouseButton::Left) {
+                            choice = Some(Choice::Play(entry.path.clone()));
+                        }
+                    }
+                    ui.next_column();
+
+                    ui.text(&im_str!("{}", header.mode));
+                    ui.next_column();
+                    ui.text(&im_str!("{}", header.score));
+                    ui.next_column();
+                    ui.text(&im_str!(
+                        "{}",
+                        utils::format_time(Duration::from_millis(header.time))
+                    ));
+                    ui.next_column();
+
+                    let date = match Local.timestamp_opt(header.date, 0).single() {
+                        Some(date) if header.date > 0 => date.format("%Y-%m-%d %H:%M").to_string(),
+                        _ => String::from("-"),
+                    };
+                    ui.text(&im_str!("{}", date));
+                    ui.next_column();
+                }
+
+                ui.columns(1, im_str!("replay_columns"), false);
+            });
+
+        if let Some(id) = clicked {
+            self.select(id);
+        }
+
+        if let Some(id) = self.selected {
+            let header = &self.entries[id].header;
+            ui.text(&im_str!(
+                "Player: {}, lines: {}, version: {}",
+                if header.player.is_empty() {
+                    "-"
+                } else {
+                    &header.player
+                },
+                header.lines,
+                header.game_version
+            ));
+
+            if ui.button(im_str!("Play"), [0.0, 0.0]) {
+                choice = Some(Choice::Play(self.entries[id].path.clone()));
+            }
+
+            ui.same_line(0.0);
+            if ui.button(im_str!("Race"), [0.0, 0.0]) {
+                choice = Some(Choice::Race(self.entries[id].path.clone()));
+            }
+
+            ui.same_line(0.0);
+            let width = ui.push_item_width(200.0);
+            ui.input_text(im_str!("##name"), &mut self.name).build();
+            width.pop(ui);
+
+            ui.same_line(0.0);
+            if ui.button(im_str!("Rename"), [0.0, 0.0]) {
+                self.rename(id);
+            }
+
+            let label = if self.json(id) {
+                im_str!("Import")
+            } else {
+                im_str!("Export JSON")
+            };
+            if ui.button(label, [0.0, 0.0]) {
+                self.convert(id);
+            }
+
+            ui.same_line(0.0);
+            if self.deleting {
+                ui.text(&im_str!("Delete {}?", self.entries[id].name));
+                ui.same_line(0.0);
+                if ui.button(im_str!("Yes"), [0.0, 0.0]) {
+                    self.delete(id);
+                }
+
+                ui.same_line(0.0);
+                if ui.button(im_str!("No"), [0.0, 0.0]) {
+                    self.deleting = false;
+                }
+            } else if ui.button(im_str!("Delete"), [0.0, 0.0]) {
+                self.deleting = true;
+            }
+        } else {
+            ui.text_disabled(im_str!("Select a replay"));
+        }
+
+        choice
+    }
+}
@@ -5,7 +5,6 @@ use std::{
 };
 
 use chrono::Utc;
-use dirs;
 use ggez::{
     audio::{self, SoundSource},
     event::{self, EventHandler, KeyMods, MouseButton},
@@ -14,15 +13,12 @@ use ggez::{
     nalgebra::{Point2, Vector2},
     timer, Context, GameResult,
 };
+use klocki_engine::{mode, replay::ReplayData, Engine};
 use rand::{thread_rng, RngCore};
 
 use crate::{
-    gameplay::Gameplay,
-    global::Global,
-    imgui_wrapper::ImGuiWrapper,
-    particles::ParticleAnimation,
-    replay::{Replay, ReplayData},
-    utils,
+    autosave, browser, gameplay::Gameplay, ghost::Ghost, global::Global,
+    imgui_wrapper::ImGuiWrapper, particles::ParticleAnimation, replay::Replay, utils,
 };
 
 pub struct Game {
@@ -38,6 +34,7 @@ pub struct Game {
     fullscreen_delay: Duration,
 
     replay: Option<Replay>,
+    ghost: Option<Ghost>,
 }
 
 impl Game {
@@ -46,10 +43,13 @@ impl Game {
         if let Some(path) = env::args().nth(1) {
             let path = PathBuf::from(path);
             if path.is_file() {
-                if let Some(replay_data) = ReplayData::load(&path) {
-                    if let Ok(r) = Replay::new(ctx, &mut g, replay_data) {
-                        replay = Some(r);
+                match ReplayData::load(&path) {
+                    Ok(replay_data) => {
+                        if let Ok(r) = Replay::new(ctx, &mut g, replay_data) {
+                            replay = Some(r);
+                        }
                     }
+                    Err(e) => log::error!("Unable to load replay {:?}: {}", path, e),
                 }
             }
         }
@@ -57,7 +57,8 @@ impl Game {
         let mut seed = [0u8; 32];
         thread_rng().fill_bytes(&mut seed);
 
-        let gameplay = Gameplay::new(ctx, &mut g, true, &seed)?;
+        let engine = Engine::new(&seed, g.settings.rules(), g.settings.mode());
+        let gameplay = Gameplay::new(ctx, &mut g, engine)?;
 
         let rect = graphics::screen_coordinates(ctx);
         let particle_animation = ParticleAnimation::new(200, 80.0, rect.w, rect.h);
@@ -67,9 +68,7 @@ impl Game {
         music.set_volume(g.settings.audio.music_volume as f32 / 100.0);
         music.play()?;
 
-        let mut path = dirs::data_local_dir().unwrap_or_default();
-        path.push("klocki");
-        path.push("replays");
+        let path = browser::replays_dir();
         fs::create_dir_all(&path)
             .unwrap_or_else(|e| log::warn!("Unable to create directory {:?}: {:?}", &path, e));
 
@@ -84,6 +83,7 @@ impl Game {
             is_fullscreen: false,
             fullscreen_delay: Duration::new(0, 0),
             replay,
+            ghost: None,
         };
 
         app.resize_event(
@@ -94,6 +94,45 @@ impl Game {
 
         Ok(app)
     }
+
+    /// Starts a new game against the replay, with its seed, rules and mode
+    fn race(&mut self, ctx: &mut Context, replay_data: &ReplayData) -> GameResult {
+        let settings = &replay_data.header.settings;
+        let mode = mode::create(&replay_data.header.mode, &settings.mode)
+            .expect("replay of an unknown mode");
+
+        let engine = Engine::new(&replay_data.seed, settings.rules(), mode);
+        self.gameplay = Gameplay::new(ctx, &mut self.g, engine)?;
+        self.game_over = false;
+        self.ghost = Some(Ghost::new(ctx, &mut self.g, replay_data)?);
+
+        self.replay = None;
+        self.g.imgui_state.playback = None;
+
+        Ok(())
+    }
+
+    /// Replay of the current game with the details of the player
+    fn replay_data(&self) -> ReplayData {
+        let mut replay_data = self.gameplay.replay_data();
+
+        let header = &mut replay_data.header;
+        header.game_version = String::from(env!("CARGO_PKG_VERSION"));
+        header.date = Utc::now().timestamp();
+        header.player = self.g.settings.gameplay.player.clone();
+        header.settings.das = self.g.settings.input.das;
+        header.settings.arr = self.g.settings.input.arr;
+
+        replay_data
+    }
+
+    fn replay_name(&self) -> String {
+        format!(
+            "Score {} - {}",
+            self.gameplay.score(),
+            Utc::now().format(autosave::TIMESTAMP),
+        )
+    }
 }
 
 impl EventHandler for Game {
@@ -116,17 +155,42 @@ impl EventHandler for Game {
         }
 
         if self.g.imgui_state.restart {
+            self.g.imgui_state.restart = false;
+
             let mut seed = [0u8; 32];
             thread_rng().fill_bytes(&mut seed);
 
-            self.gameplay = Gameplay::new(ctx, &mut self.g, true, &seed)?;
+            let engine = Engine::new(&seed, self.g.settings.rules(), self.g.settings.mode());
+            self.gameplay = Gameplay::new(ctx, &mut self.g, engine)?;
             self.game_over = false;
+            self.ghost = None;
         }
 
         if self.g.settings_state.restart {
             event::quit(ctx);
         }
 
+        if let Some(path) = self.g.imgui_state.play_replay.take() {
+            match ReplayData::load(&path) {
+                Ok(replay_data) => self.replay = Some(Replay::new(ctx, &mut self.g, replay_data)?),
+                Err(e) => log::error!("Unable to load replay {:?}: {}", path, e),
+            }
+        }
+
+        if let Some(path) = self.g.imgui_state.race_replay.take() {
+            match ReplayData::load(&path) {
+                Ok(replay_data) => self.race(ctx, &replay_data)?,
+                Err(e) => log::error!("Unable to load replay {:?}: {}", path, e),
+            }
+        }
+
+        if self.g.imgui_state.exit_replay {
+            self.g.imgui_state.exit_replay = false;
+            self.g.imgui_state.playback = None;
+            self.replay = None;
+            self.g.imgui_state.restart = true;
+        }
+
         if self.g.settings.graphics.animated_background {
             self.particle_animation.update(ctx)?;
         }
@@ -140,16 +204,18 @@ impl EventHandler for Game {
             self.g.sfx.set_volume(self.g.settings.audio.sfx_volume);
         }
 
-        let mut gameplay = &mut self.gameplay;
+        let gameplay = if let Some(replay) = &mut self.replay {
+            replay.update(ctx, &mut self.g)?;
+            &mut replay.gameplay
+        } else {
+            self.gameplay.update(ctx, &mut self.g, true)?;
 
-        if let Some(replay) = &mut self.replay {
-            if !replay.gameplay.paused() && !self.g.imgui_state.paused {
-                replay.update(ctx);
+            if let Some(ghost) = &mut self.ghost {
+                ghost.update(ctx, &mut self.g, self.gameplay.engine().ticks())?;
             }
-            gameplay = &mut replay.gameplay;
-        }
 
-        gameplay.update(ctx, &mut self.g, true)?;
+            &mut self.gameplay
+        };
 
         if let Some(explosion) = gameplay.explosion() {
             self.particle_animation.explode(explosion);
@@ -159,22 +225,32 @@ impl EventHandler for Game {
             if self.gameplay.game_over() && !self.game_over {
                 self.game_over = true;
                 self.g.imgui_state.game_over_window = true;
-                self.g.imgui_state.replay_score = self.gameplay.score();
+                self.g.imgui_state.result = Some(self.gameplay.result());
+
+                let mode = self.gameplay.record_name();
+                self.g.imgui_state.best = self.g.records.best(&mode);
+                self.g.imgui_state.new_record = match self.gameplay.record() {
+                    Some(record) => self.g.records.submit(&mode, record),
+                    None => false,
+                };
+
+                let new_record = self.g.imgui_state.new_record;
+                let replays = &self.g.settings.replays;
+                self.g.imgui_state.replay_saved = autosave::should_save(replays, new_record);
+                if self.g.imgui_state.replay_saved {
+                    let replay_data = self.replay_data();
+                    let name = self.replay_name();
+                    autosave::save(&self.g.settings.replays, &replay_data, &name, new_record);
+                }
             }
 
             if self.g.imgui_state.save_replay {
                 self.g.imgui_state.save_replay = false;
-                let mut path = dirs::data_local_dir().unwrap_or_default();
-                path.push("klocki");
-                path.push("replays");
-                path.push(format!(
-                    "Score {} - {}.klocki",
-                    self.gameplay.score(),
-                    Utc::now().format("%Y%m%d_%H%M%S"),
-                ));
-
-                self.gameplay.replay_data().save(&path);
-                ReplayData::load(&path).unwrap();
+                self.g.imgui_state.replay_saved = true;
+
+                let mut path = browser::replays_dir();
+                path.push(format!("{}.klocki", self.replay_name()));
+                self.replay_data().save(&path);
             }
         }
 
@@ -208,20 +284,31 @@ impl EventHandler for Game {
         }
 
         let screen = graphics::screen_coordinates(ctx);
+        let stack = self.gameplay.stack();
         let position_center = Point2::new(
-            (screen.w - (self.gameplay.stack.width * self.g.settings.gameplay.block_size) as f32)
-                / 2.0,
-            (screen.h - (self.gameplay.stack.height * self.g.settings.gameplay.block_size) as f32)
-                / 2.0,
+            (screen.w - (stack.width * self.g.settings.gameplay.block_size) as f32) / 2.0,
+            (screen.h - (stack.height * self.g.settings.gameplay.block_size) as f32) / 2.0,
         );
 
-        let gameplay = if let Some(replay) = &mut self.replay {
-            &mut replay.gameplay
+        if let Some(replay) = &mut self.replay {
+            replay.gameplay.draw(ctx, &self.g, position_center)?;
+        } else if let Some(ghost) = &mut self.ghost {
+            if self.g.settings.gameplay.ghost_overlay {
+                self.gameplay.draw(ctx, &self.g, position_center)?;
+                ghost.draw(ctx, &self.g, position_center)?;
+            } else {
+                // Each board takes about its width in the hold and next pieces around it
+                let offset = Vector2::new(
+                    (self.g.settings.gameplay.block_size * (stack.width + 10)) as f32 / 2.0,
+                    0.0,
+                );
+
+                self.gameplay.draw(ctx, &self.g, position_center - offset)?;
+                ghost.draw(ctx, &self.g, position_center + offset)?;
+            }
         } else {
-            &mut self.gameplay
-        };
-
-        gameplay.draw(ctx, &self.g, position_center)?;
+            self.gameplay.draw(ctx, &self.g, position_center)?;
+        }
 
         self.imgui_wrapper.draw(ctx, &mut self.g);
 
@@ -231,7 +318,13 @@ impl EventHandler for Game {
         Ok(())
     }
 
-    fn mouse_button_down_event(&mut self, _ctx: &mut Context, button: MouseButton, _x: f32, _y: f32) {
+    fn mouse_button_down_event(
+        &mut self,
+        _ctx: &mut Context,
+        button: MouseButton,
+        _x: f32,
+        _y: f32,
+    ) {
         self.imgui_wrapper.update_mouse_down((
             button == MouseButton::Left,
             button == MouseButton::Right,
@@ -265,6 +358,15 @@ impl EventHandler for Game {
             KeyCode::LAlt => self.g.settings.graphics.hide_menu ^= true,
             _ => (),
         };
+
+        if let Some(playback) = self.g.imgui_state.playback.as_mut() {
+            match keycode {
+                KeyCode::Space => playback.paused ^= true,
+                KeyCode::Right => playback.step = true,
+                KeyCode::Left => playback.rewind = true,
+                _ => (),
+            }
+        }
     }
 
     fn resize_event(&mut self, ctx: &mut Context, width: f32, height: f32) {
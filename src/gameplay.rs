@@ -1,4 +1,4 @@
-use std::{collections::VecDeque, time::Duration};
+use std::time::Duration;
 
 use ggez::{
     graphics::{self, Color, Font, Scale},
@@ -6,22 +6,19 @@ use ggez::{
     nalgebra::{Point2, Vector2},
     timer, Context, GameResult,
 };
+use klocki_engine::{
+    action::Action,
+    engine::INITIAL_ACTIONS,
+    piece::{Piece, TSpin},
+    replay::{ReplayData, ReplaySettings},
+    shape::Shape,
+    stack::Stack,
+    End, Engine, Event,
+};
 
 use crate::{
-    action::Action,
-    bag::Bag,
-    blocks::Blocks,
-    global::Global,
-    holder::Holder,
-    input::Input,
-    particles::Explosion,
-    piece::Piece,
-    popups::Popup,
-    popups::Popups,
-    replay::ReplayData,
-    score::Score,
-    stack::{Locked, Stack},
-    utils,
+    blocks::Blocks, global::Global, hud, input::Input, particles::Explosion, popups::Popup,
+    popups::Popups, records::Record, stack_view::StackView, utils,
 };
 
 #[derive(PartialEq)]
@@ -34,25 +31,11 @@ enum Countdown {
 pub struct Gameplay {
     interactive: bool,
     input: Input,
-    action_duration: Duration,
 
-    actions: VecDeque<Action>,
-    replay: ReplayData,
-
-    pub stack: Stack,
-    bag: Bag,
-    piece: Piece,
-    piece_visible: bool,
-    holder: Holder,
-    score: Score,
+    engine: Engine,
+    stack_view: StackView,
     popups: Popups,
 
-    game_over: bool,
-    falling: Duration,
-    fall_interval: Duration,
-
-    piece_entering: Option<Duration>,
-
     font: Font,
     blocks: Blocks,
 
@@ -61,36 +44,41 @@ pub struct Gameplay {
     countdown_switch: Duration,
 }
 
+fn draw_piece(
+    ctx: &mut Context,
+    blocks: &mut Blocks,
+    piece: &Piece,
+    position: Point2<f32>,
+    vanish: i32,
+    block_size: i32,
+    alpha: f32,
+) -> GameResult {
+    let position = Point2::new(
+        position[0] + (piece.x * block_size) as f32,
+        position[1] + ((piece.y - vanish) * block_size) as f32,
+    );
+
+    blocks.draw_shape(ctx, piece.grid(), position, block_size, alpha)
+}
+
 impl Gameplay {
-    pub fn new(
-        ctx: &mut Context,
-        g: &mut Global,
-        interactive: bool,
-        seed: &[u8; 32],
-    ) -> GameResult<Gameplay> {
+    pub fn new(ctx: &mut Context, g: &mut Global, engine: Engine) -> GameResult<Gameplay> {
         let mut input = Input::new();
         input
             .bind(KeyCode::Right, Action::MoveRight, true)
             .bind(KeyCode::Left, Action::MoveLeft, true)
-            .bind(KeyCode::Down, Action::MoveDown, true)
+            .bind_hold(KeyCode::Down, Action::SoftDropStart, Action::SoftDropStop)
             .bind(KeyCode::Up, Action::RotateClockwise, false)
             .bind(KeyCode::X, Action::RotateClockwise, false)
             .bind(KeyCode::Z, Action::RotateCounterClockwise, false)
+            .bind(KeyCode::A, Action::Rotate180, false)
             .bind(KeyCode::Space, Action::HardDrop, false)
-            .bind(KeyCode::LShift, Action::SoftDrop, false)
+            .bind(KeyCode::LShift, Action::SonicDrop, false)
             .bind(KeyCode::C, Action::HoldPiece, false)
             .exclude(KeyCode::Right, KeyCode::Left)
             .exclude(KeyCode::Left, KeyCode::Right);
 
-        let actions = VecDeque::new();
-        let replay = ReplayData::new(seed);
-
-        let stack = Stack::new(10, 20, 20);
-
-        let mut bag = Bag::new(seed);
-        let piece = Piece::new(bag.pop(), &stack);
-        let holder = Holder::default();
-        let score = Score::default();
+        let interactive = !engine.replaying();
         let popups = Popups::new(ctx)?;
 
         let font = Font::new(ctx, utils::path(ctx, "fonts/bold.ttf"))?;
@@ -100,20 +88,9 @@ impl Gameplay {
         Ok(Gameplay {
             interactive,
             input,
-            action_duration: Duration::new(0, 0),
-            actions,
-            replay,
-            stack,
-            bag,
-            piece,
-            piece_visible: true,
-            holder,
-            score,
+            engine,
+            stack_view: StackView::new(),
             popups,
-            game_over: false,
-            falling: Duration::new(0, 0),
-            fall_interval: Duration::from_secs(1),
-            piece_entering: None,
             font,
             blocks,
             explosion: None,
@@ -122,34 +99,16 @@ impl Gameplay {
         })
     }
 
-    fn reset_fall(&mut self) {
-        if self.falling > self.fall_interval {
-            self.falling -= self.fall_interval
-        } else {
-            self.falling = Duration::new(0, 0);
-        }
-    }
-
-    pub fn explode(&mut self, color: Color) {
+    pub fn explode(&mut self, color: Color, strength: f32) {
         self.explosion = Some(Explosion {
             position: Point2::new(960.0, 540.0),
             color,
-            strength: 30.0,
+            strength,
         });
     }
 
     pub fn action(&mut self, action: Action, immediate: bool) {
-        if immediate {
-            self.actions.push_front(action);
-        } else {
-            self.actions.push_back(action);
-        }
-    }
-
-    pub fn actions(&mut self, actions: &[Action]) {
-        for &action in actions {
-            self.action(action, false);
-        }
+        self.engine.action(action, immediate);
     }
 
     pub fn explosion(&mut self) -> Option<Explosion> {
@@ -158,190 +117,225 @@ impl Gameplay {
         result
     }
 
-    pub fn replay_data(&self) -> &ReplayData {
-        &self.replay
+    pub fn replay_data(&self) -> ReplayData {
+        self.engine.replay_data()
     }
 
-    pub fn score(&self) -> i32 {
-        self.score.score()
+    pub fn engine(&self) -> &Engine {
+        &self.engine
     }
 
-    pub fn game_over(&self) -> bool {
-        self.game_over
+    pub fn engine_mut(&mut self) -> &mut Engine {
+        &mut self.engine
     }
 
-    pub fn paused(&self) -> bool {
-        self.game_over || self.countdown != Countdown::Finished || self.stack.blocked()
+    pub fn stack(&self) -> &Stack {
+        self.engine.stack()
     }
 
-    fn process_action(&mut self, g: &mut Global, action: Action, sfx: bool) -> bool {
-        match action {
-            Action::HoldPiece => {
-                if let Some(shape) = self.holder.hold(self.piece.shape(), &mut self.bag) {
-                    self.piece = Piece::new(shape, &self.stack);
-                    if sfx {
-                        g.sfx.play("hold");
-                    }
-                } else if sfx {
-                    g.sfx.play("holdfail");
-                }
-            }
-            Action::FallPiece => {
-                if !self.piece.shift(0, 1, &self.stack) && self.interactive {
-                    self.action(Action::LockPiece, true);
-                }
-            }
-            Action::LockPiece => {
-                match self.stack.lock(
-                    &self.piece,
-                    Duration::from_millis(g.settings.gameplay.clear_delay.into()),
-                ) {
-                    Locked::Collision => {
-                        if self.interactive {
-                            self.action(Action::GameOver, true);
-                        }
-                    }
-                    Locked::Success(rows) => {
-                        if rows > 0 {
-                            let t_spin = self.piece.t_spin(&self.stack);
-                            self.score.lock(rows, t_spin);
-                            self.popups.lock(
-                                rows,
-                                t_spin,
-                                self.score.btb(),
-                                self.score.combo(),
-                                g.settings.gameplay.entry_delay.into(),
-                            );
-
-                            let color = if rows == 4 {
-                                Color::new(0.0, 1.0, 1.0, 1.0)
-                            } else if t_spin {
-                                Color::new(1.0, 0.0, 1.0, 1.0)
-                            } else {
-                                Color::new(0.5, 0.5, 0.0, 1.0)
-                            };
-
-                            self.explode(color);
-                        } else {
-                            self.score.reset_combo();
-                        }
+    pub fn score(&self) -> i32 {
+        self.engine.score().score()
+    }
 
-                        if sfx {
-                            match (rows, self.piece.t_spin(&self.stack)) {
-                                (1, false) => g.sfx.play("erase1"),
-                                (2, false) => g.sfx.play("erase2"),
-                                (3, false) => g.sfx.play("erase3"),
-                                (4, false) => g.sfx.play("erase4"),
-                                (0, true) => g.sfx.play("tspin0"),
-                                (1, true) => g.sfx.play("tspin1"),
-                                (2, true) => g.sfx.play("tspin2"),
-                                (3, true) => g.sfx.play("tspin3"),
-                                _ => g.sfx.play("lock"),
-                            }
-                        }
+    /// Name under which personal bests are kept, timed modes are separated by their length.
+    /// Games with other settings than the default ones get a hash of the settings appended,
+    /// so they are only compared with the games played by the same rules
+    pub fn record_name(&self) -> String {
+        let mode = self.engine.mode();
 
-                        self.piece_entering = Some(Duration::new(0, 0));
-                        self.piece_visible = false;
+        let name = match mode.time_limit() {
+            Some(limit) => format!("{} {}s", mode.name(), limit.as_secs()),
+            None => String::from(mode.name()),
+        };
 
-                        return false;
-                    }
-                };
-            }
-            Action::GameOver => {
-                self.game_over = true;
-                self.stack.game_over();
-                self.explode(Color::new(1.0, 0.0, 0.0, 1.0));
+        // Handling of the keys doesn't change the game and the time limit is already in the name
+        let defaults = ReplaySettings::default();
+        let mut settings = self.engine.replay_data().header.settings;
+        settings.das = defaults.das;
+        settings.arr = defaults.arr;
+        settings.mode.time_limit = defaults.mode.time_limit;
 
-                let mut popup = Popup::new(Duration::from_secs(10));
-                popup.add("Game Over", Color::new(0.9, 0.1, 0.2, 1.0), 4.0);
-                self.popups.add(popup);
+        if settings == defaults {
+            return name;
+        }
 
-                if sfx {
-                    g.sfx.play("gameover");
-                }
+        // FNV-1a, unlike the hasher of the standard library it is the same in every build
+        let hash = format!("{:?}", settings)
+            .bytes()
+            .fold(0x811c_9dc5_u32, |hash, byte| {
+                (hash ^ u32::from(byte)).wrapping_mul(0x0100_0193)
+            });
+        format!("{} #{:08x}", name, hash)
+    }
 
-                return false;
-            }
-            Action::MoveLeft
-            | Action::MoveRight
-            | Action::MoveDown
-            | Action::RotateClockwise
-            | Action::RotateCounterClockwise
-            | Action::SoftDrop
-            | Action::HardDrop => self.process_movement_action(g, action, sfx),
+    /// Final result of the game as shown to the player, modes with a line goal are timed
+    pub fn result(&self) -> Record {
+        if self.engine.mode().line_goal().is_some() {
+            Record::time(self.engine.time())
+        } else {
+            Record::Score(self.score())
+        }
+    }
+
+    /// Result of the game if it counts for a personal best
+    pub fn record(&self) -> Option<Record> {
+        let mode = self.engine.mode();
+        let end = self.engine.end()?;
+
+        let counts = match end {
+            End::Goal => true,
+            End::TimeUp => mode.time_limit().is_some(),
+            End::TopOut => mode.line_goal().is_none() && mode.time_limit().is_none(),
         };
 
-        true
+        if counts {
+            Some(self.result())
+        } else {
+            None
+        }
     }
 
-    fn process_movement_action(&mut self, g: &mut Global, action: Action, sfx: bool) {
-        match action {
-            Action::MoveRight => {
-                let moved = self.piece.shift(1, 0, &self.stack);
-                if moved && self.piece.touching_floor(&self.stack) {
-                    self.reset_fall();
-                }
+    pub fn game_over(&self) -> bool {
+        self.engine.game_over()
+    }
 
-                if sfx && moved {
+    /// Countdown has finished and the game is running
+    pub fn started(&self) -> bool {
+        self.countdown == Countdown::Finished
+    }
+
+    pub fn paused(&self) -> bool {
+        self.engine.paused() || self.countdown != Countdown::Finished
+    }
+
+    fn process_event(&mut self, g: &mut Global, event: Event, sfx: bool) {
+        match event {
+            Event::Moved => {
+                if sfx {
                     g.sfx.play("move");
                 }
             }
-            Action::MoveLeft => {
-                let moved = self.piece.shift(-1, 0, &self.stack);
-                if moved && self.piece.touching_floor(&self.stack) {
-                    self.reset_fall();
+            Event::Rotated => {
+                if sfx {
+                    g.sfx.play("rotate");
                 }
-
-                if sfx && moved {
-                    g.sfx.play("move");
+            }
+            Event::Held => {
+                if sfx {
+                    g.sfx.play("hold");
                 }
             }
-            Action::MoveDown => {
-                if self.piece.shift(0, 1, &self.stack) {
-                    self.reset_fall();
+            Event::HoldFailed => {
+                if sfx {
+                    g.sfx.play("holdfail");
+                }
+            }
+            Event::Locked {
+                rows,
+                t_spin,
+                perfect_clear,
+                btb,
+                combo,
+                ..
+            } => {
+                if rows > 0 {
+                    self.popups.lock(
+                        rows,
+                        t_spin,
+                        perfect_clear,
+                        btb,
+                        combo,
+                        self.engine.rules().entry_delay.as_millis() as u64,
+                    );
+
+                    if perfect_clear {
+                        self.explode(Color::new(1.0, 0.85, 0.2, 1.0), 60.0);
+                    } else {
+                        let color = if rows == 4 {
+                            Color::new(0.0, 1.0, 1.0, 1.0)
+                        } else if t_spin != TSpin::None {
+                            Color::new(1.0, 0.0, 1.0, 1.0)
+                        } else {
+                            Color::new(0.5, 0.5, 0.0, 1.0)
+                        };
 
-                    if sfx {
-                        g.sfx.play("move");
+                        self.explode(color, 30.0);
                     }
                 }
-            }
-            Action::RotateClockwise => {
-                let rotated = self.piece.rotate(true, &self.stack);
-                if rotated && self.piece.touching_floor(&self.stack) {
-                    self.reset_fall();
+
+                if sfx {
+                    match (rows, t_spin == TSpin::None) {
+                        (1, true) => g.sfx.play("erase1"),
+                        (2, true) => g.sfx.play("erase2"),
+                        (3, true) => g.sfx.play("erase3"),
+                        (4, true) => g.sfx.play("erase4"),
+                        (0, false) => g.sfx.play("tspin0"),
+                        (1, false) => g.sfx.play("tspin1"),
+                        (2, false) => g.sfx.play("tspin2"),
+                        (3, false) => g.sfx.play("tspin3"),
+                        _ => g.sfx.play("lock"),
+                    }
                 }
+            }
+            Event::RowsCleared(rows) => {
+                let vanish = self.engine.stack().vanish;
+                self.stack_view.destroy(&rows, vanish);
+            }
+            Event::LevelUp(level) => {
+                let mut popup = Popup::new(Duration::from_secs(2));
+                popup.add("Level up\n", Color::new(0.5, 0.8, 1.0, 1.0), 2.0);
+                popup.add(&format!("{}", level), Color::new(0.8, 0.9, 1.0, 1.0), 4.0);
+                self.popups.add(popup);
 
-                if sfx && rotated {
-                    g.sfx.play("rotate");
+                if sfx {
+                    g.sfx.play("levelup");
                 }
             }
-            Action::RotateCounterClockwise => {
-                let rotated = self.piece.rotate(false, &self.stack);
-                if rotated && self.piece.touching_floor(&self.stack) {
-                    self.reset_fall();
+            Event::Attack(_) | Event::GarbageReceived(_) => (),
+            Event::Finished => {
+                let mut popup = Popup::new(Duration::from_secs(10));
+                popup.add("Finished\n", Color::new(0.5, 0.9, 0.7, 1.0), 4.0);
+
+                if self.engine.mode().line_goal().is_some() {
+                    popup.add(
+                        &utils::format_time(self.engine.time()),
+                        Color::new(0.8, 0.9, 1.0, 1.0),
+                        2.0,
+                    );
                 }
 
-                if sfx && rotated {
-                    g.sfx.play("rotate");
+                self.popups.add(popup);
+
+                if sfx {
+                    g.sfx.play("levelup");
                 }
             }
-            Action::SoftDrop => {
-                let rows = self.piece.fall(&self.stack);
-                if rows > 0 {
-                    self.reset_fall();
-                    self.score.soft_drop(rows);
+            Event::TimeUp => {
+                let mut popup = Popup::new(Duration::from_secs(10));
+                popup.add("Time's up\n", Color::new(0.5, 0.8, 1.0, 1.0), 4.0);
+                popup.add(
+                    &format!("{}", self.score()),
+                    Color::new(0.8, 0.9, 1.0, 1.0),
+                    2.0,
+                );
+                self.popups.add(popup);
+
+                if sfx {
+                    g.sfx.play("levelup");
                 }
             }
-            Action::HardDrop => {
-                let rows = self.piece.fall(&self.stack);
-                self.score.hard_drop(rows);
+            Event::GameOver => {
+                self.explode(Color::new(1.0, 0.0, 0.0, 1.0), 30.0);
+
+                let mut popup = Popup::new(Duration::from_secs(10));
+                popup.add("Game Over", Color::new(0.9, 0.1, 0.2, 1.0), 4.0);
+                self.popups.add(popup);
 
-                if self.interactive {
-                    self.action(Action::LockPiece, true);
+                if sfx {
+                    g.sfx.play("gameover");
                 }
             }
-            _ => (),
-        };
+        }
     }
 
     pub fn update(&mut self, ctx: &mut Context, g: &mut Global, sfx: bool) -> GameResult {
@@ -350,11 +344,15 @@ impl Gameplay {
         }
 
         if g.imgui_state.debug_t_spin_tower {
-            self.stack.debug_t_spin();
+            self.engine.stack_mut().debug_t_spin();
+        }
+
+        if g.imgui_state.debug_garbage {
+            self.action(Action::Garbage(4), false);
         }
 
         if g.imgui_state.debug_tetris_tower {
-            self.stack.debug_tetris();
+            self.engine.stack_mut().debug_tetris();
         }
 
         if g.settings_state.skin_switched {
@@ -395,71 +393,46 @@ impl Gameplay {
             }
         }
 
+        let stack = self.engine.stack();
         self.popups.update(
             ctx,
-            (g.settings.gameplay.block_size * self.stack.width) as f32,
-            (g.settings.gameplay.block_size * self.stack.height) as f32,
+            (g.settings.gameplay.block_size * stack.width) as f32,
+            (g.settings.gameplay.block_size * stack.height) as f32,
             g.settings.gameplay.block_size as f32,
         )?;
 
-        self.stack.update(ctx, g)?;
-
-        self.input.update(
-            ctx,
-            g.settings.input.das,
-            g.settings.input.arr,
-            self.paused() || g.imgui_state.paused || self.piece_entering.is_some(),
-        );
-
-        if self.paused() || g.imgui_state.paused {
-            return Ok(());
-        }
-
-        let actions = self.input.actions();
-        self.actions(&actions);
-
-        self.action_duration += timer::delta(ctx);
-
-        if self.piece_entering.is_none() {
-            while let Some(action) = self.actions.pop_front() {
-                self.replay.add(action, self.action_duration);
-                self.action_duration = Duration::new(0, 0);
-
-                if !self.process_action(g, action, sfx) {
-                    break;
-                }
-            }
-        }
-
-        self.piece.update(ctx, &self.stack);
-
-        if let Some(entering) = self.piece_entering.as_mut() {
-            *entering += timer::delta(ctx);
-
-            if *entering >= Duration::from_millis(g.settings.gameplay.entry_delay.into()) {
-                self.piece_entering = None;
-                self.piece_visible = true;
+        self.stack_view.update(ctx);
 
-                self.piece = Piece::new(self.bag.pop(), &self.stack);
-                if self.stack.collision(&self.piece) && self.interactive {
-                    self.action(Action::GameOver, true);
+        if self.interactive {
+            self.input.update(
+                ctx,
+                g.settings.input.das,
+                g.settings.input.arr,
+                self.paused() || g.imgui_state.paused || self.engine.piece_entering(),
+            );
+
+            if !self.paused() && !g.imgui_state.paused {
+                let actions = self.input.actions();
+                self.engine.actions(&actions);
+
+                // Keys pressed during the entry delay were paused, the engine applies the held
+                // ones when the next piece spawns
+                let actions = if g.settings.input.initial_actions {
+                    self.input.held(ctx, &INITIAL_ACTIONS)
                 } else {
-                    self.reset_fall();
-                    self.holder.unlock();
-                }
+                    vec![]
+                };
+                self.engine.initial_actions(&actions);
             }
-        } else if self.interactive {
-            if self.piece.locking() > Duration::from_millis(g.settings.gameplay.lock_delay.into()) {
-                self.action(Action::LockPiece, true);
-            } else {
-                self.falling += timer::delta(ctx);
+        }
 
-                if self.falling >= self.fall_interval {
-                    self.falling -= self.fall_interval;
+        // Replays are advanced by their playback controls
+        if self.interactive && self.started() && !g.imgui_state.paused {
+            self.engine.update(timer::delta(ctx));
+        }
 
-                    self.action(Action::FallPiece, true);
-                }
-            }
+        for event in self.engine.events() {
+            self.process_event(g, event, sfx);
         }
 
         Ok(())
@@ -468,82 +441,206 @@ impl Gameplay {
     pub fn draw(&mut self, ctx: &mut Context, g: &Global, position: Point2<f32>) -> GameResult<()> {
         let block_size = g.settings.gameplay.block_size;
 
-        let next_block_size = block_size / 2;
+        let board = self.engine.board();
+        // Longer previews get smaller blocks to keep clear of the values under them
+        let next_block_size = block_size * 3 / board.preview.max(6) as i32;
         let holder_block_size = block_size * 3 / 4;
         let ui_color = Color::new(0.8, 0.9, 1.0, 0.8);
         let ui_scale = Scale::uniform(block_size as f32);
 
-        self.holder.draw(
-            ctx,
-            position + Vector2::new(-6.0 * holder_block_size as f32, 0.0),
-            &mut self.blocks,
-            holder_block_size,
-            ui_color,
-            self.font,
-        )?;
+        let stack = self.engine.stack();
 
-        self.bag.draw(
-            ctx,
-            position + Vector2::new((self.stack.width * block_size) as f32, 0.0),
-            &mut self.blocks,
-            next_block_size,
-            ui_color,
-            self.font,
-        )?;
+        if board.hold {
+            hud::draw_hold(
+                ctx,
+                self.engine.holder(),
+                position + Vector2::new(-6.0 * holder_block_size as f32, 0.0),
+                &mut self.blocks,
+                holder_block_size,
+                ui_color,
+                self.font,
+            )?;
+        }
+
+        if board.preview > 0 {
+            let rotation_system = self.engine.rotation_system();
+            let next: Vec<Shape> = self
+                .engine
+                .bag()
+                .peek(board.preview as usize)
+                .map(|&shape| Shape::new(shape, rotation_system))
+                .collect();
 
-        self.score.draw(
+            hud::draw_next(
+                ctx,
+                &next,
+                position + Vector2::new((stack.width * block_size) as f32, 0.0),
+                &mut self.blocks,
+                next_block_size,
+                ui_color,
+                self.font,
+            )?;
+        }
+
+        hud::draw_score(
             ctx,
+            self.engine.score(),
             position
                 + Vector2::new(
-                    (block_size * self.stack.width) as f32 + next_block_size as f32,
-                    (block_size * self.stack.height) as f32 - ui_scale.y * 3.0,
+                    (block_size * stack.width) as f32 + next_block_size as f32,
+                    (block_size * stack.height) as f32 - ui_scale.y * 3.0,
                 ),
             ui_color,
             self.font,
             ui_scale,
         )?;
 
+        let values_position = |row: f32| {
+            position
+                + Vector2::new(
+                    (block_size * stack.width) as f32 + next_block_size as f32,
+                    (block_size * stack.height) as f32 - ui_scale.y * 3.0 * row,
+                )
+        };
+
+        let mode = self.engine.mode();
+        let values = if let Some(goal) = mode.line_goal() {
+            vec![
+                ("Lines", format!("{}/{}", self.engine.lines(), goal)),
+                ("Pieces", format!("{}", self.engine.pieces())),
+                ("Time", utils::format_time(self.engine.time())),
+            ]
+        } else if let Some(time_left) = self.engine.time_left() {
+            vec![
+                ("Lines", format!("{}", self.engine.lines())),
+                ("Time", utils::format_time(time_left)),
+            ]
+        } else {
+            vec![("Level", format!("{}", self.engine.level()))]
+        };
+
+        for (row, (title, value)) in values.into_iter().enumerate() {
+            hud::draw_value(
+                ctx,
+                title,
+                value,
+                values_position(row as f32 + 2.0),
+                ui_color,
+                self.font,
+                ui_scale,
+            )?;
+        }
+
+        hud::draw_garbage(
+            ctx,
+            self.engine.garbage().pending(),
+            position,
+            block_size,
+            stack.height,
+        )?;
+
+        if let Some(left) = self.engine.piece().resets_left() {
+            if self.engine.piece_visible() {
+                hud::draw_lock_resets(
+                    ctx,
+                    left,
+                    position,
+                    block_size,
+                    (stack.width, stack.height),
+                )?;
+            }
+        }
+
         // https://github.com/ggez/ggez/issues/664
         ggez::graphics::pop_transform(ctx);
         ggez::graphics::apply_transformations(ctx)?;
 
-        self.stack
-            .draw(ctx, position, &mut self.blocks, block_size)?;
+        self.stack_view
+            .draw(ctx, g, stack, board.buffer, position, &mut self.blocks)?;
+
+        if self.engine.piece_visible() {
+            let piece = self.engine.piece();
 
-        if self.piece_visible && !self.game_over {
-            let alpha = if g.settings.gameplay.lock_delay > 0 {
-                1.0 - self.piece.locking().as_millis() as f32
-                    / g.settings.gameplay.lock_delay as f32
+            let lock_delay = self.engine.rules().lock_delay;
+            let alpha = if lock_delay.as_millis() > 0 {
+                1.0 - piece.locking().as_millis() as f32 / lock_delay.as_millis() as f32
             } else {
                 1.0
             };
 
-            self.piece.draw(
+            draw_piece(
                 ctx,
-                position,
-                self.stack.vanish,
                 &mut self.blocks,
+                piece,
+                position,
+                stack.vanish,
                 block_size,
                 alpha,
             )?;
 
             if g.settings.gameplay.ghost_piece > 0 {
-                let mut ghost = self.piece.clone();
-                if ghost.fall(&self.stack) > 0 {
-                    ghost.draw(
+                let mut ghost = piece.clone();
+                if ghost.fall(stack) > 0 {
+                    let alpha = g.settings.gameplay.ghost_piece as f32 / 100.0;
+                    draw_piece(
                         ctx,
-                        position,
-                        self.stack.vanish,
                         &mut self.blocks,
+                        &ghost,
+                        position,
+                        stack.vanish,
                         block_size,
-                        g.settings.gameplay.ghost_piece as f32 / 100.0,
+                        alpha,
                     )?;
                 }
             }
         }
 
         self.popups
-            .draw(ctx, position, (block_size * self.stack.height) as f32)?;
+            .draw(ctx, position, (block_size * stack.height) as f32)?;
+
+        Ok(())
+    }
+
+    /// Draws only the blocks of the stack and the piece, to be laid over another board
+    pub fn draw_overlay(
+        &mut self,
+        ctx: &mut Context,
+        g: &Global,
+        position: Point2<f32>,
+        alpha: f32,
+    ) -> GameResult {
+        let block_size = g.settings.gameplay.block_size;
+        let stack = self.engine.stack();
+
+        self.blocks.clear();
+        for y in 0..stack.height {
+            for x in 0..stack.width {
+                let block = stack.grid()[(stack.vanish + y) as usize][x as usize];
+                if block == 0 {
+                    continue;
+                }
+
+                let destination = Point2::new(
+                    position[0] + (x * block_size) as f32,
+                    position[1] + (y * block_size) as f32,
+                );
+
+                self.blocks.add(block, block_size, destination, alpha);
+            }
+        }
+        self.blocks.draw(ctx)?;
+
+        if self.engine.piece_visible() {
+            draw_piece(
+                ctx,
+                &mut self.blocks,
+                self.engine.piece(),
+                position,
+                stack.vanish,
+                block_size,
+                alpha,
+            )?;
+        }
 
         Ok(())
     }
@@ -553,11 +650,13 @@ impl Gameplay {
             return;
         }
 
+        let stack = self.engine.stack_mut();
+
         let mouse = utils::mouse_position_coords(ctx);
         let screen = graphics::screen_coordinates(ctx);
         let position_center = Vector2::new(
-            (screen.w - (self.stack.width * g.settings.gameplay.block_size) as f32) / 2.0,
-            (screen.h - (self.stack.height * g.settings.gameplay.block_size) as f32) / 2.0,
+            (screen.w - (stack.width * g.settings.gameplay.block_size) as f32) / 2.0,
+            (screen.h - (stack.height * g.settings.gameplay.block_size) as f32) / 2.0,
         );
 
         let position = mouse - position_center;
@@ -571,11 +670,11 @@ impl Gameplay {
         let x = x as i32;
         let y = y as i32;
 
-        if x >= self.stack.width || y >= self.stack.height {
+        if x >= stack.width || y >= stack.height {
             return;
         }
 
-        let y = y + self.stack.vanish;
-        self.stack.place_random(x as usize, y as usize);
+        let y = y + stack.vanish;
+        stack.place_random(x as usize, y as usize);
     }
 }
@@ -0,0 +1,39 @@
+use ggez::{nalgebra::Point2, Context, GameResult};
+use klocki_engine::{replay::ReplayData, Engine};
+
+use crate::{gameplay::Gameplay, global::Global};
+
+/// Board of a replay raced against, kept on the same tick as the live game
+pub struct Ghost {
+    pub gameplay: Gameplay,
+}
+
+impl Ghost {
+    pub fn new(ctx: &mut Context, g: &mut Global, replay_data: &ReplayData) -> GameResult<Ghost> {
+        let engine = Engine::from_replay(replay_data);
+
+        Ok(Ghost {
+            gameplay: Gameplay::new(ctx, g, engine)?,
+        })
+    }
+
+    pub fn update(&mut self, ctx: &mut Context, g: &mut Global, ticks: u32) -> GameResult {
+        let engine = self.gameplay.engine_mut();
+        while engine.ticks() < ticks {
+            engine.tick();
+        }
+
+        self.gameplay.update(ctx, g, false)?;
+        self.gameplay.explosion();
+
+        Ok(())
+    }
+
+    pub fn draw(&mut self, ctx: &mut Context, g: &Global, position: Point2<f32>) -> GameResult {
+        if g.settings.gameplay.ghost_overlay {
+            self.gameplay.draw_overlay(ctx, g, position, 0.3)
+        } else {
+            self.gameplay.draw(ctx, g, position)
+        }
+    }
+}
@@ -1,5 +1,6 @@
 use crate::{
     imgui_wrapper::ImGuiState,
+    records::Records,
     settings::{Settings, SettingsState},
     sfx::Sfx,
 };
@@ -9,6 +10,7 @@ pub struct Global {
     pub settings_state: SettingsState,
     pub sfx: Sfx,
     pub imgui_state: ImGuiState,
+    pub records: Records,
 }
 
 impl Global {
@@ -18,6 +20,7 @@ impl Global {
             settings_state: SettingsState::default(),
             sfx: Sfx::default(),
             imgui_state: ImGuiState::default(),
+            records: Records::new(),
         }
     }
 }
@@ -0,0 +1,188 @@
+use ggez::{
+    graphics::Align,
+    graphics::{self, Color, DrawMode, DrawParam, Font, Mesh, Rect, Scale, Text, TextFragment},
+    nalgebra::{Point2, Vector2},
+    Context, GameResult,
+};
+use klocki_engine::{holder::Holder, piece::MOVE_RESETS, score::Score, shape::Shape};
+
+use crate::blocks::Blocks;
+
+fn draw_title(
+    ctx: &mut Context,
+    title: &str,
+    position: Point2<f32>,
+    block_size: i32,
+    text_color: Color,
+    font: Font,
+) -> GameResult {
+    let mut text = Text::new(TextFragment {
+        text: title.to_string(),
+        color: Some(text_color),
+        font: Some(font),
+        scale: Some(Scale::uniform(block_size as f32 * 2.0)),
+    });
+
+    text.set_bounds(
+        Point2::new(block_size as f32 * 6.0, block_size as f32),
+        Align::Center,
+    );
+
+    graphics::draw(ctx, &text, DrawParam::new().dest(position))
+}
+
+pub fn draw_next(
+    ctx: &mut Context,
+    next: &[Shape],
+    position: Point2<f32>,
+    blocks: &mut Blocks,
+    block_size: i32,
+    text_color: Color,
+    font: Font,
+) -> GameResult {
+    draw_title(ctx, "Next", position, block_size, text_color, font)?;
+
+    let position = position + Vector2::new(0.0, block_size as f32 * 2.5);
+
+    for (i, shape) in next.iter().enumerate() {
+        let position = position
+            + Vector2::new(
+                block_size as f32 * 3.0 - shape.grids[0].width as f32 * block_size as f32 / 2.0,
+                (i as i32 * block_size * 3) as f32,
+            );
+        blocks.draw_shape(ctx, &shape.grids[0], position, block_size, 0.9)?;
+    }
+
+    Ok(())
+}
+
+pub fn draw_hold(
+    ctx: &mut Context,
+    holder: &Holder,
+    position: Point2<f32>,
+    blocks: &mut Blocks,
+    block_size: i32,
+    text_color: Color,
+    font: Font,
+) -> GameResult {
+    draw_title(ctx, "Hold", position, block_size, text_color, font)?;
+
+    let position = position + Vector2::new(0.0, block_size as f32 * 2.5);
+
+    if let Some(shape) = holder.shape() {
+        let position = position
+            + Vector2::new(
+                block_size as f32 * 3.0 - shape.grids[0].width as f32 * block_size as f32 / 2.0,
+                0.0,
+            );
+        blocks.draw_shape(ctx, &shape.grids[0], position, block_size, 1.0)?;
+    }
+
+    Ok(())
+}
+
+/// Bar of the incoming garbage growing from the bottom of the stack
+pub fn draw_garbage(
+    ctx: &mut Context,
+    lines: u32,
+    position: Point2<f32>,
+    block_size: i32,
+    stack_height: i32,
+) -> GameResult {
+    if lines == 0 {
+        return Ok(());
+    }
+
+    let lines = (lines as i32).min(stack_height);
+    let width = block_size as f32 / 4.0;
+    let height = (lines * block_size) as f32;
+
+    let meter = Mesh::new_rectangle(
+        ctx,
+        DrawMode::fill(),
+        Rect::new(
+            position.x - width,
+            position.y + (stack_height * block_size) as f32 - height,
+            width,
+            height,
+        ),
+        Color::new(0.9, 0.1, 0.2, 0.8),
+    )?;
+
+    graphics::draw(ctx, &meter, DrawParam::new())
+}
+
+/// Bar under the stack that shrinks with every lock delay reset used up
+pub fn draw_lock_resets(
+    ctx: &mut Context,
+    left: u32,
+    position: Point2<f32>,
+    block_size: i32,
+    stack_size: (i32, i32),
+) -> GameResult {
+    if left == 0 {
+        return Ok(());
+    }
+
+    let (stack_width, stack_height) = stack_size;
+    let fraction = left as f32 / MOVE_RESETS as f32;
+    let width = (stack_width * block_size) as f32 * fraction;
+    let height = block_size as f32 / 8.0;
+
+    let meter = Mesh::new_rectangle(
+        ctx,
+        DrawMode::fill(),
+        Rect::new(
+            position.x,
+            position.y + (stack_height * block_size) as f32 + height,
+            width,
+            height,
+        ),
+        Color::new(1.0 - fraction, fraction, 0.2, 0.8),
+    )?;
+
+    graphics::draw(ctx, &meter, DrawParam::new())
+}
+
+pub fn draw_value(
+    ctx: &mut Context,
+    title: &str,
+    value: String,
+    position: Point2<f32>,
+    color: Color,
+    font: Font,
+    scale: Scale,
+) -> GameResult {
+    let mut text = Text::new(TextFragment {
+        text: format!("{}\n", title),
+        color: Some(color),
+        font: Some(font),
+        scale: Some(Scale::uniform(scale.x * 1.5)),
+    });
+
+    text.add(TextFragment::from(value));
+    text.set_font(font, scale);
+
+    graphics::draw(ctx, &text, DrawParam::new().dest(position))?;
+
+    Ok(())
+}
+
+pub fn draw_score(
+    ctx: &mut Context,
+    score: &Score,
+    position: Point2<f32>,
+    color: Color,
+    font: Font,
+    scale: Scale,
+) -> GameResult {
+    draw_value(
+        ctx,
+        "Score",
+        format!("{}", score.score()),
+        position,
+        color,
+        font,
+        scale,
+    )
+}
@@ -1,15 +1,23 @@
 use std::{
     io::Read,
+    path::PathBuf,
     time::{Duration, Instant},
 };
 
 use gfx_core::{handle::RenderTargetView, memory::Typed};
-use gfx_device_gl;
 use ggez::{event, filesystem, graphics, timer, Context};
-use imgui::{self, im_str, Condition, FontId, FontSource, ImString, StyleColor, Window};
+use imgui::{self, im_str, Condition, FontId, FontSource, ImString, Slider, StyleColor, Window};
 use imgui_gfx_renderer::{Renderer, Shaders};
 
-use crate::{global::Global, utils};
+use klocki_engine::playback::SPEEDS;
+
+use crate::{
+    browser::{Browser, Choice},
+    global::Global,
+    records::Record,
+    replay::PlaybackState,
+    utils,
+};
 
 #[derive(Default)]
 struct MouseState {
@@ -26,13 +34,22 @@ pub struct ImGuiState {
     pub game_over: bool,
     pub debug_t_spin_tower: bool,
     pub debug_tetris_tower: bool,
+    pub debug_garbage: bool,
     pub update_last: Duration,
     pub draw_last: Duration,
     pub update: Vec<Duration>,
     pub draw: Vec<Duration>,
     pub game_over_window: bool,
     pub save_replay: bool,
-    pub replay_score: i32,
+    pub replay_saved: bool,
+    pub result: Option<Record>,
+    pub best: Option<Record>,
+    pub new_record: bool,
+    /// Present while a replay is played
+    pub playback: Option<PlaybackState>,
+    pub play_replay: Option<PathBuf>,
+    pub race_replay: Option<PathBuf>,
+    pub exit_replay: bool,
 }
 
 pub struct ImGuiWrapper {
@@ -43,6 +60,8 @@ pub struct ImGuiWrapper {
     last_frame: Instant,
     mouse_state: MouseState,
     show_debug_window: bool,
+    show_browser: bool,
+    browser: Browser,
 }
 
 impl ImGuiWrapper {
@@ -157,6 +176,8 @@ impl ImGuiWrapper {
             last_frame: Instant::now(),
             mouse_state: MouseState::default(),
             show_debug_window: false,
+            show_browser: false,
+            browser: Browser::new(),
         }
     }
 
@@ -214,6 +235,9 @@ impl ImGuiWrapper {
                         g.imgui_state.debug_tetris_tower =
                             ui.button(im_str!("Tetris tower"), [0.0, 0.0]);
 
+                        g.imgui_state.debug_garbage =
+                            ui.button(im_str!("Receive garbage"), [0.0, 0.0]);
+
                         ui.separator();
                         ui.text(im_str!("Window size: {}x{}", w, h));
 
@@ -235,14 +259,27 @@ impl ImGuiWrapper {
                 Window::new(im_str!("Game over"))
                     .opened(&mut opened)
                     .resizable(false)
-                    .size([120.0, 180.0], Condition::Appearing)
-                    .position([w / 2.0 - 60.0, h / 3.0 * 2.0], Condition::Appearing)
+                    .size([160.0, 180.0], Condition::Appearing)
+                    .position([w / 2.0 - 80.0, h / 3.0 * 2.0], Condition::Appearing)
                     .collapsible(false)
                     .build(&ui, || {
-                        ui.text(im_str!("Score: {}", g.imgui_state.replay_score));
+                        if let Some(result) = g.imgui_state.result {
+                            ui.text(im_str!("{}: {}", result.label(), result));
+                        }
+
+                        if g.imgui_state.new_record {
+                            ui.text_colored([1.0, 0.8, 0.2, 1.0], im_str!("Personal best!"));
+                        } else if let Some(best) = g.imgui_state.best {
+                            ui.text(im_str!("Best: {}", best));
+                        }
                         ui.separator();
 
-                        g.imgui_state.save_replay = ui.button(im_str!("Save replay"), [0.0, 0.0]);
+                        if g.imgui_state.replay_saved {
+                            ui.text(im_str!("Replay saved"));
+                        } else {
+                            g.imgui_state.save_replay =
+                                ui.button(im_str!("Save replay"), [0.0, 0.0]);
+                        }
 
                         ui.separator();
                         if g.imgui_state.save_replay || ui.button(im_str!("Close"), [0.0, 0.0]) {
@@ -255,9 +292,87 @@ impl ImGuiWrapper {
                 }
             }
 
+            if self.show_browser {
+                let browser = &mut self.browser;
+                let mut opened = true;
+                Window::new(im_str!("Replays"))
+                    .opened(&mut opened)
+                    .size([640.0, 400.0], Condition::Appearing)
+                    .position([w / 2.0 - 320.0, 80.0], Condition::Appearing)
+                    .collapsible(false)
+                    .build(&ui, || match browser.draw(&ui) {
+                        Some(Choice::Play(path)) => g.imgui_state.play_replay = Some(path),
+                        Some(Choice::Race(path)) => g.imgui_state.race_replay = Some(path),
+                        None => (),
+                    });
+
+                let chosen =
+                    g.imgui_state.play_replay.is_some() || g.imgui_state.race_replay.is_some();
+                if !opened || chosen {
+                    self.show_browser = false;
+                }
+            }
+
+            if let Some(playback) = g.imgui_state.playback.as_mut() {
+                let mut opened = true;
+                Window::new(im_str!("Replay"))
+                    .opened(&mut opened)
+                    .resizable(false)
+                    .size([420.0, 0.0], Condition::Appearing)
+                    .position([w / 2.0 - 210.0, h - 150.0], Condition::Appearing)
+                    .collapsible(false)
+                    .build(&ui, || {
+                        let label = if playback.paused {
+                            im_str!("Play")
+                        } else {
+                            im_str!("Pause")
+                        };
+
+                        if ui.button(label, [60.0, 0.0]) {
+                            playback.paused ^= true;
+                        }
+
+                        ui.same_line(0.0);
+                        playback.rewind = ui.button(im_str!("<< 5 s"), [0.0, 0.0]);
+
+                        ui.same_line(0.0);
+                        playback.step = ui.button(im_str!("Next piece"), [0.0, 0.0]);
+
+                        for (i, speed) in SPEEDS.iter().enumerate() {
+                            ui.same_line(0.0);
+                            if ui.radio_button_bool(&im_str!("{}x", speed), playback.speed == i) {
+                                playback.speed = i;
+                            }
+                        }
+
+                        let mut time = playback.time.as_secs_f32();
+                        let length = playback.length.as_secs_f32();
+                        let format = im_str!(
+                            "{} / {}",
+                            utils::format_time(playback.time),
+                            utils::format_time(playback.length)
+                        );
+
+                        ui.push_item_width(-1.0);
+                        if Slider::new(im_str!("##progress"), 0.0..=length)
+                            .display_format(&format)
+                            .build(&ui, &mut time)
+                        {
+                            playback.seek = Some(Duration::from_secs_f32(time));
+                        }
+                    });
+
+                g.imgui_state.exit_replay = !opened;
+            }
+
             if !g.settings.graphics.hide_menu {
                 if let Some(menu_bar) = ui.begin_main_menu_bar() {
                     if let Some(menu) = ui.begin_menu(im_str!("File"), true) {
+                        if imgui::MenuItem::new(im_str!("Replays")).build(&ui) {
+                            self.show_browser = true;
+                            self.browser.refresh();
+                        }
+
                         if imgui::MenuItem::new(im_str!("Quit")).build(&ui) {
                             event::quit(ctx);
                         }
@@ -265,6 +380,10 @@ impl ImGuiWrapper {
                         menu.end(&ui);
                     }
 
+                    if g.settings.draw_modes(&g.settings_state, &ui) {
+                        g.imgui_state.restart = true;
+                    }
+
                     g.settings.draw(&mut g.settings_state, &ui, self.bold_font);
 
                     ui.separator();
@@ -1,14 +1,15 @@
 use std::{collections::HashMap, time::Duration};
 
 use ggez::{self, input::keyboard::KeyCode, timer, Context};
-
-use crate::action::Action;
+use klocki_engine::action::Action;
 
 const MAX_KEYCODES: usize = 161;
 
 struct KeyBind {
     actions: Vec<Action>,
     repeat: bool,
+    /// Sent when the key is let go, such keys are never paused so that both ends are sent
+    release_actions: Vec<Action>,
 }
 
 pub struct Input {
@@ -46,6 +47,7 @@ impl Input {
                     KeyBind {
                         actions: vec![action],
                         repeat,
+                        release_actions: vec![],
                     },
                 );
             }
@@ -57,6 +59,16 @@ impl Input {
         self
     }
 
+    /// Binds a pair of actions to pressing and letting go of the key
+    pub fn bind_hold(&mut self, keycode: KeyCode, press: Action, release: Action) -> &mut Input {
+        self.bind(keycode, press, false);
+        if let Some(bind) = self.key_binds.get_mut(&keycode) {
+            bind.release_actions.push(release);
+        }
+
+        self
+    }
+
     pub fn exclude(&mut self, keycode: KeyCode, excludes: KeyCode) -> &mut Input {
         if let Some(exclusions) = self.exclusions.get_mut(&keycode) {
             exclusions.push(excludes);
@@ -77,7 +89,7 @@ impl Input {
 
         let mut ignore: Vec<KeyCode> = vec![];
         for exclusion in &self.exclusions {
-            if pressed_keys.contains(&exclusion.0) {
+            if pressed_keys.contains(exclusion.0) {
                 ignore.extend(exclusion.1);
             }
         }
@@ -86,6 +98,10 @@ impl Input {
             let key = *keycode as usize;
 
             if !pressed_keys.contains(keycode) {
+                if self.key_activated[key].is_some() {
+                    self.actions.extend(&bind.release_actions);
+                }
+
                 self.key_activated[key] = None;
                 self.key_repeated[key] = None;
                 continue;
@@ -99,7 +115,7 @@ impl Input {
 
             match self.key_activated[key].as_mut() {
                 None => {
-                    if paused {
+                    if paused && bind.release_actions.is_empty() {
                         self.key_activated[key] = Some(das);
                     } else {
                         self.key_activated[key] = Some(zero);
@@ -136,6 +152,21 @@ impl Input {
         }
     }
 
+    /// Which of the given actions have their keys held down, in the given order
+    pub fn held(&self, ctx: &Context, actions: &[Action]) -> Vec<Action> {
+        let pressed_keys = ggez::input::keyboard::pressed_keys(ctx);
+
+        actions
+            .iter()
+            .filter(|action| {
+                self.key_binds.iter().any(|(keycode, bind)| {
+                    pressed_keys.contains(keycode) && bind.actions.contains(action)
+                })
+            })
+            .copied()
+            .collect()
+    }
+
     pub fn actions(&mut self) -> Vec<Action> {
         self.actions.drain(..).collect()
     }
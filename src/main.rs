@@ -1,27 +1,27 @@
-mod action;
-mod bag;
+mod autosave;
 mod blocks;
+mod browser;
 mod game;
 mod gameplay;
+mod ghost;
 mod global;
-mod holder;
+mod hud;
 mod imgui_wrapper;
 mod input;
 mod particles;
-mod piece;
 mod popups;
+mod records;
 mod replay;
-mod score;
+mod scoring;
 mod settings;
 mod sfx;
-mod shape;
-mod stack;
+mod stack_view;
 mod utils;
+mod verify;
 
-use std::{ffi::OsStr, panic, thread};
+use std::{env, ffi::OsStr, panic, process, thread};
 
 use backtrace::Backtrace;
-use env_logger;
 use ggez::{conf, event, filesystem, graphics, ContextBuilder, GameResult};
 use imgui::ImString;
 use log::{self, LevelFilter};
@@ -29,6 +29,15 @@ use log::{self, LevelFilter};
 use crate::{game::Game, global::Global, sfx::Sfx};
 
 fn main() {
+    if env::args().nth(1).as_deref() == Some("verify") {
+        env_logger::builder()
+            .default_format_timestamp(false)
+            .filter_module("klocki", LevelFilter::Warn)
+            .init();
+
+        process::exit(verify::run(env::args().skip(2).collect()));
+    }
+
     std::env::set_var("WINIT_UNIX_BACKEND", "x11");
 
     env_logger::builder()
@@ -137,6 +146,13 @@ fn real_main() -> GameResult {
             .position(|s| s.to_str() == g.settings.gameplay.skin)
             .unwrap_or_default();
 
+        g.settings_state.player = ImString::with_capacity(32);
+        g.settings_state
+            .player
+            .push_str(&g.settings.gameplay.player);
+
+        g.settings_state.score_tables = scoring::tables().into_iter().map(|t| t.name).collect();
+
         g.sfx = Sfx::load(ctx, g.settings.audio.sfx_volume)?;
 
         let game = &mut Game::new(ctx, g)?;
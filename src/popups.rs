@@ -7,6 +7,8 @@ use ggez::{
     timer, Context, GameResult,
 };
 
+use klocki_engine::piece::TSpin;
+
 use crate::utils;
 
 #[derive(Default)]
@@ -173,16 +175,37 @@ impl Popups {
         }
     }
 
-    pub fn lock(&mut self, rows: i32, t_spin: bool, btb: bool, combo: Option<i32>, delay: u64) {
+    pub fn lock(
+        &mut self,
+        rows: i32,
+        t_spin: TSpin,
+        perfect_clear: bool,
+        btb: bool,
+        combo: Option<i32>,
+        delay: u64,
+    ) {
         let mut lifetime = delay;
         if lifetime < 750 {
             lifetime = 750;
         }
 
+        if perfect_clear {
+            lifetime *= 2;
+        }
+
         let mut popup = Popup::new(Duration::from_millis(lifetime * 2));
 
-        if t_spin {
-            popup.add("T-Spin\n", Color::new(1.0, 0.5, 0.9, 1.0), 4.0);
+        if perfect_clear {
+            popup.add("Perfect Clear\n", Color::new(1.0, 0.85, 0.2, 1.0), 5.0);
+        }
+
+        if t_spin != TSpin::None {
+            let text = if t_spin == TSpin::Mini {
+                "T-Spin Mini\n"
+            } else {
+                "T-Spin\n"
+            };
+            popup.add(text, Color::new(1.0, 0.5, 0.9, 1.0), 4.0);
 
             match rows {
                 1 => popup.add("Single\n", Color::new(0.8, 0.9, 1.0, 1.0), 2.0),
@@ -0,0 +1,106 @@
+use std::{collections::BTreeMap, fmt, fs, path::PathBuf, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+use crate::utils;
+
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq)]
+pub enum Record {
+    /// Time in milliseconds, lower is better
+    Time(u64),
+    /// Score, higher is better
+    Score(i32),
+}
+
+impl Record {
+    pub fn time(time: Duration) -> Record {
+        Record::Time(time.as_millis() as u64)
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Record::Time(_) => "Time",
+            Record::Score(_) => "Score",
+        }
+    }
+
+    fn better_than(self, other: Record) -> bool {
+        match (self, other) {
+            (Record::Time(a), Record::Time(b)) => a < b,
+            (Record::Score(a), Record::Score(b)) => a > b,
+            _ => true,
+        }
+    }
+}
+
+impl fmt::Display for Record {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Record::Time(millis) => {
+                write!(f, "{}", utils::format_time(Duration::from_millis(millis)))
+            }
+            Record::Score(score) => write!(f, "{}", score),
+        }
+    }
+}
+
+/// Personal bests of the player for every mode
+#[derive(Serialize, Deserialize, Default)]
+pub struct Records {
+    best: BTreeMap<String, Record>,
+}
+
+impl Records {
+    pub fn new() -> Records {
+        Records::load().unwrap_or_default()
+    }
+
+    fn path() -> PathBuf {
+        let mut path = dirs::data_local_dir().unwrap_or_default();
+        path.push("klocki");
+        path.push("records.toml");
+        path
+    }
+
+    pub fn save(&self) {
+        let toml = toml::to_string(self).unwrap();
+        let path = Records::path();
+
+        if let Err(e) = fs::write(&path, toml) {
+            log::error!("Unable to save records: {:?}", e);
+        } else {
+            log::info!("Saved records to: {:?}", &path);
+        }
+    }
+
+    fn load() -> Option<Records> {
+        let contents = fs::read_to_string(Records::path()).ok()?;
+
+        match toml::from_str(&contents) {
+            Ok(records) => Some(records),
+            Err(e) => {
+                log::error!("Error while reading records: {:?}", e);
+                None
+            }
+        }
+    }
+
+    pub fn best(&self, mode: &str) -> Option<Record> {
+        self.best.get(mode).copied()
+    }
+
+    /// Returns true if the record is a new personal best
+    pub fn submit(&mut self, mode: &str, record: Record) -> bool {
+        let new_best = match self.best(mode) {
+            Some(best) => record.better_than(best),
+            None => true,
+        };
+
+        if new_best {
+            self.best.insert(String::from(mode), record);
+            self.save();
+        }
+
+        new_best
+    }
+}
@@ -1,125 +1,97 @@
-use std::{
-    collections::VecDeque,
-    fs,
-    io::{Read, Write},
-    path::Path,
-    time::Duration,
-};
+use std::time::Duration;
 
-use flate2::{read::GzDecoder, write::GzEncoder, Compression};
 use ggez::{timer, Context, GameResult};
-use serde::{Deserialize, Serialize};
-
-use crate::{action::Action, gameplay::Gameplay, global::Global};
-
-#[derive(Serialize, Deserialize, Copy, Clone)]
-pub struct TimedAction {
-    action: Action,
-    duration: Duration,
-}
+use klocki_engine::{
+    engine::TICK,
+    playback::{Playback, SPEEDS},
+    replay::ReplayData,
+    Engine,
+};
 
-#[derive(Serialize, Deserialize, Clone)]
-pub struct ReplayData {
-    pub seed: [u8; 32],
-    pub actions: VecDeque<TimedAction>,
+use crate::{gameplay::Gameplay, global::Global};
+
+/// Requests of the playback window together with the position of the replay shown in it
+pub struct PlaybackState {
+    pub paused: bool,
+    /// Index in `SPEEDS`
+    pub speed: usize,
+    pub step: bool,
+    pub rewind: bool,
+    pub seek: Option<Duration>,
+    pub time: Duration,
+    pub length: Duration,
 }
 
-impl ReplayData {
-    pub fn new(seed: &[u8; 32]) -> ReplayData {
-        let mut seed_clone = [0; 32];
-        seed_clone.clone_from_slice(seed);
-
-        ReplayData {
-            actions: VecDeque::new(),
-            seed: seed_clone,
-        }
-    }
-
-    pub fn add(&mut self, action: Action, duration: Duration) {
-        self.actions.push_back(TimedAction { action, duration });
-    }
-
-    pub fn current_duration(&self) -> Option<Duration> {
-        if let Some(action) = self.actions.get(0) {
-            Some(action.duration)
-        } else {
-            None
-        }
-    }
-
-    pub fn pop_action(&mut self) -> Action {
-        if let Some(action) = self.actions.pop_front() {
-            action.action
-        } else {
-            Action::GameOver
-        }
-    }
-
-    pub fn save(&self, path: &Path) {
-        let mut writer = GzEncoder::new(Vec::new(), Compression::best());
-        let bytes = bincode::serialize(&self).unwrap();
-        writer.write_all(&bytes).unwrap();
-
-        if let Err(e) = fs::write(path, writer.finish().unwrap()) {
-            log::error!("Unable to save replay: {:?}", e)
-        } else {
-            log::info!("Saved replay in {:?}", path);
+impl Default for PlaybackState {
+    fn default() -> PlaybackState {
+        PlaybackState {
+            paused: false,
+            speed: 2,
+            step: false,
+            rewind: false,
+            seek: None,
+            time: Duration::new(0, 0),
+            length: Duration::new(0, 0),
         }
     }
-
-    pub fn load(path: &Path) -> Option<ReplayData> {
-        match fs::read(path) {
-            Err(e) => log::error!("Unable to load replay: {:?}", e),
-            Ok(bytes) => {
-                let mut reader = GzDecoder::new(&bytes[..]);
-                let mut bytes: Vec<u8> = vec![];
-
-                match reader.read_to_end(&mut bytes) {
-                    Err(e) => log::error!("Unable to decompress replay: {:?}", e),
-                    Ok(_) => {
-                        let replay_data: Result<ReplayData, _> = bincode::deserialize(&bytes);
-
-                        match replay_data {
-                            Err(e) => log::error!("Unable to deserialize replay: {:?}", e),
-                            Ok(replay_data) => {
-                                log::info!("Loaded replay from {:?}", path,);
-                                return Some(replay_data);
-                            }
-                        }
-                    }
-                }
-            }
-        }
-
-        None
-    }
 }
 
 pub struct Replay {
-    replay_data: ReplayData,
-    action_duration: Duration,
     pub gameplay: Gameplay,
+    playback: Playback,
 }
 
 impl Replay {
     pub fn new(ctx: &mut Context, g: &mut Global, replay_data: ReplayData) -> GameResult<Replay> {
+        let engine = Engine::from_replay(&replay_data);
+        let playback = Playback::new(&engine, &replay_data);
+
+        g.imgui_state.playback = Some(PlaybackState {
+            length: TICK * playback.length(),
+            ..PlaybackState::default()
+        });
+
         Ok(Replay {
-            gameplay: Gameplay::new(ctx, g, false, &replay_data.seed)?,
-            replay_data,
-            action_duration: Duration::new(0, 0),
+            gameplay: Gameplay::new(ctx, g, engine)?,
+            playback,
         })
     }
 
-    pub fn update(&mut self, ctx: &mut Context) {
-        self.action_duration += timer::delta(ctx);
+    pub fn update(&mut self, ctx: &mut Context, g: &mut Global) -> GameResult {
+        const REWIND: Duration = Duration::from_secs(5);
+
+        if let Some(state) = g.imgui_state.playback.as_mut() {
+            let started = self.gameplay.started();
+            let engine = self.gameplay.engine_mut();
+
+            self.playback.paused = state.paused;
+            self.playback.speed = SPEEDS[state.speed.min(SPEEDS.len() - 1)];
 
-        while let Some(duration) = self.replay_data.current_duration() {
-            if self.action_duration >= duration {
-                self.gameplay.action(self.replay_data.pop_action(), false);
-                self.action_duration = Duration::new(0, 0);
-            } else {
-                break;
+            if state.step {
+                state.step = false;
+                self.playback.step(engine);
             }
+
+            if state.rewind {
+                state.rewind = false;
+                let ticks = (REWIND.as_micros() / TICK.as_micros()) as u32;
+                self.playback
+                    .seek(engine, engine.ticks().saturating_sub(ticks));
+            }
+
+            if let Some(time) = state.seek.take() {
+                let tick = (time.as_micros() / TICK.as_micros()) as u32;
+                self.playback.seek(engine, tick);
+            }
+
+            if started && !g.imgui_state.paused {
+                self.playback.update(engine, timer::delta(ctx));
+            }
+
+            state.paused = self.playback.paused;
+            state.time = TICK * engine.ticks().min(self.playback.length());
         }
+
+        self.gameplay.update(ctx, g, true)
     }
 }
@@ -0,0 +1,67 @@
+use std::{ffi::OsStr, fs, path::PathBuf};
+
+use klocki_engine::score::ScoreTable;
+
+/// Score tables of the player, one per file, in the same format as `ScoreTable`:
+///
+/// ```toml
+/// name = "Flat"
+/// clear = [100, 300, 500, 800]
+/// t_spin = [0, 800, 1200, 1600]
+/// t_spin_mini = [0, 200, 400]
+/// perfect_clear = [800, 1200, 1800, 2000]
+/// back_to_back = 150
+/// combo = 50
+/// soft_drop = 1
+/// hard_drop = 2
+/// level_multiplier = false
+/// ```
+fn dir() -> PathBuf {
+    let mut path = dirs::data_local_dir().unwrap_or_default();
+    path.push("klocki");
+    path.push("scoring");
+    path
+}
+
+fn load_custom() -> Vec<ScoreTable> {
+    let dir = match fs::read_dir(dir()) {
+        Ok(dir) => dir,
+        Err(_) => return vec![],
+    };
+
+    let mut tables = vec![];
+    for path in dir.filter_map(|e| e.ok()).map(|e| e.path()) {
+        if path.extension() != Some(OsStr::new("toml")) {
+            continue;
+        }
+
+        let table = fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|contents| toml::from_str(&contents).map_err(|e| e.to_string()));
+
+        match table {
+            Ok(table) => tables.push(table),
+            Err(e) => log::error!("Unable to load score table {:?}: {}", path, e),
+        }
+    }
+
+    tables.sort_by(|a: &ScoreTable, b| a.name.cmp(&b.name));
+    tables
+}
+
+/// Built-in score tables followed by the ones of the player
+pub fn tables() -> Vec<ScoreTable> {
+    let mut tables = ScoreTable::built_in();
+    tables.extend(load_custom());
+    tables
+}
+
+pub fn find(name: &str) -> ScoreTable {
+    tables()
+        .into_iter()
+        .find(|t| t.name == name)
+        .unwrap_or_else(|| {
+            log::warn!("Unknown score table {:?}, using the guideline one", name);
+            ScoreTable::guideline()
+        })
+}
@@ -1,22 +1,35 @@
-use std::{fs, path::PathBuf};
+use std::{collections::BTreeMap, env, fs, path::PathBuf, time::Duration};
 
-use dirs;
 use ggez::{conf::NumSamples, graphics::Image, Context, GameResult};
 use imgui::{self, im_str, ComboBox, FontId, ImStr, ImString, Slider, Ui};
+use klocki_engine::{
+    gravity::Gravity,
+    mode::{
+        self, Board, CommonSettings, GameMode, ModeSettings, MAX_BUFFER, MAX_HEIGHT, MAX_PREVIEW,
+        MAX_WIDTH, MIN_HEIGHT, MIN_WIDTH,
+    },
+    piece::{LockReset, LOCK_RESETS},
+    randomizer::{RandomizerType, RANDOMIZERS},
+    rotation::{RotationSystem, ROTATION_SYSTEMS},
+    Rules,
+};
 use serde::{Deserialize, Serialize};
-use toml;
 
-use crate::utils;
+use crate::{scoring, utils};
 
-#[derive(Serialize, Deserialize)]
+/// Entries missing in the config files of the older versions get their defaults
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
 pub struct Settings {
     pub graphics: Graphics,
     pub gameplay: Gameplay,
     pub audio: Audio,
     pub input: Input,
+    pub replays: Replays,
 }
 
 #[derive(Serialize, Deserialize)]
+#[serde(default)]
 pub struct Graphics {
     pub window_size: (u32, u32),
     pub fullscreen: bool,
@@ -27,27 +40,74 @@ pub struct Graphics {
 }
 
 #[derive(Serialize, Deserialize)]
+#[serde(default)]
 pub struct Gameplay {
+    pub player: String,
+    pub mode: String,
     pub block_size: i32,
     pub ghost_piece: u32,
+    /// Draw the board of the raced replay over the own one instead of next to it
+    pub ghost_overlay: bool,
     pub entry_delay: u32,
     pub lock_delay: u32,
+    pub lock_reset: LockReset,
     pub clear_delay: u32,
+    pub start_level: u32,
+    pub ultra_time: u32,
+    pub gravity: Vec<f32>,
     pub skin: String,
     pub stack_grid: bool,
     pub stack_outline: bool,
+    /// Name of the score table for every mode
+    pub scoring: BTreeMap<String, String>,
+    pub rotation_system: BTreeMap<String, RotationSystem>,
+    pub randomizer: BTreeMap<String, RandomizerType>,
+    pub board: BTreeMap<String, Board>,
 }
 
 #[derive(Serialize, Deserialize)]
+#[serde(default)]
 pub struct Audio {
     pub music_volume: u32,
     pub sfx_volume: u32,
 }
 
 #[derive(Serialize, Deserialize)]
+#[serde(default)]
 pub struct Input {
     pub das: u32,
     pub arr: u32,
+    /// Rotate and hold the next piece when it spawns with the keys held down
+    pub initial_actions: bool,
+    /// Multiple of the gravity while soft dropping
+    pub soft_drop_factor: u32,
+    /// Soft drop right onto the stack, ignoring the factor
+    pub instant_soft_drop: bool,
+}
+
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq)]
+pub enum AutoSave {
+    Off,
+    Always,
+    PersonalBest,
+    /// Every game, but only the last `keep_last` of them are kept
+    Last,
+}
+
+pub static AUTO_SAVES: [(AutoSave, &str); 4] = [
+    (AutoSave::Off, "Off"),
+    (AutoSave::Always, "Every game"),
+    (AutoSave::PersonalBest, "Personal bests"),
+    (AutoSave::Last, "Last games"),
+];
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct Replays {
+    pub auto_save: AutoSave,
+    pub keep_last: u32,
+    /// Size cap of the replays directory in megabytes
+    pub max_size: u32,
 }
 
 #[derive(Default)]
@@ -56,6 +116,8 @@ pub struct SettingsState {
     pub skins_imstr: Vec<ImString>,
     pub skin_id: usize,
     pub skin_switched: bool,
+    pub player: ImString,
+    pub score_tables: Vec<String>,
     pub restart: bool,
 }
 
@@ -68,37 +130,81 @@ static SAMPLINGS: [NumSamples; 6] = [
     NumSamples::Sixteen,
 ];
 
+impl Default for Graphics {
+    fn default() -> Graphics {
+        Graphics {
+            window_size: (800, 800),
+            fullscreen: false,
+            multi_sampling: NumSamples::Zero,
+            vsync: true,
+            animated_background: true,
+            hide_menu: false,
+        }
+    }
+}
+
+impl Default for Gameplay {
+    fn default() -> Gameplay {
+        Gameplay {
+            player: env::var("USER")
+                .or_else(|_| env::var("USERNAME"))
+                .unwrap_or_else(|_| String::from("Player")),
+            mode: String::from("Marathon"),
+            block_size: 43,
+            ghost_piece: 10,
+            ghost_overlay: false,
+            entry_delay: 0,
+            lock_delay: 500,
+            lock_reset: LockReset::Infinite,
+            clear_delay: 250,
+            start_level: 1,
+            ultra_time: 120,
+            gravity: Gravity::guideline().levels,
+            skin: String::from("nblox.png"),
+            stack_grid: true,
+            stack_outline: true,
+            scoring: BTreeMap::new(),
+            rotation_system: BTreeMap::new(),
+            randomizer: BTreeMap::new(),
+            board: BTreeMap::new(),
+        }
+    }
+}
+
+impl Default for Audio {
+    fn default() -> Audio {
+        Audio {
+            music_volume: 50,
+            sfx_volume: 50,
+        }
+    }
+}
+
+impl Default for Input {
+    fn default() -> Input {
+        Input {
+            das: 133,
+            arr: 33,
+            initial_actions: false,
+            soft_drop_factor: 20,
+            instant_soft_drop: false,
+        }
+    }
+}
+
+impl Default for Replays {
+    fn default() -> Replays {
+        Replays {
+            auto_save: AutoSave::Off,
+            keep_last: 20,
+            max_size: 50,
+        }
+    }
+}
+
 impl Settings {
     pub fn new() -> Settings {
-        if let Some(settings) = Settings::load() {
-            settings
-        } else {
-            Settings {
-                graphics: Graphics {
-                    window_size: (800, 800),
-                    fullscreen: false,
-                    multi_sampling: NumSamples::Zero,
-                    vsync: true,
-                    animated_background: true,
-                    hide_menu: false,
-                },
-                gameplay: Gameplay {
-                    block_size: 43,
-                    ghost_piece: 10,
-                    entry_delay: 0,
-                    lock_delay: 500,
-                    clear_delay: 250,
-                    skin: String::from("nblox.png"),
-                    stack_grid: true,
-                    stack_outline: true,
-                },
-                audio: Audio {
-                    music_volume: 50,
-                    sfx_volume: 50,
-                },
-                input: Input { das: 133, arr: 33 },
-            }
-        }
+        Settings::load().unwrap_or_default()
     }
 
     fn path() -> PathBuf {
@@ -119,8 +225,11 @@ impl Settings {
         let path = Settings::path();
 
         if let Ok(contents) = fs::read_to_string(&path) {
-            if let Ok(settings) = toml::from_str(&contents) {
+            if let Ok(mut settings) = toml::from_str::<Settings>(&contents) {
                 log::info!("Loaded settings from: {:?}", &path);
+                for board in settings.gameplay.board.values_mut() {
+                    *board = board.clamped();
+                }
                 return Some(settings);
             } else {
                 log::error!("Error while reading config file");
@@ -132,6 +241,184 @@ impl Settings {
         None
     }
 
+    pub fn rules(&self) -> Rules {
+        Rules {
+            entry_delay: Duration::from_millis(self.gameplay.entry_delay.into()),
+            lock_delay: Duration::from_millis(self.gameplay.lock_delay.into()),
+            clear_delay: Duration::from_millis(self.gameplay.clear_delay.into()),
+            lock_reset: self.gameplay.lock_reset,
+            soft_drop_factor: if self.input.instant_soft_drop {
+                0
+            } else {
+                self.input.soft_drop_factor
+            },
+        }
+    }
+
+    pub fn scoring(&self) -> &str {
+        self.gameplay
+            .scoring
+            .get(&self.gameplay.mode)
+            .map_or("Guideline", String::as_str)
+    }
+
+    pub fn rotation_system(&self) -> RotationSystem {
+        self.gameplay
+            .rotation_system
+            .get(&self.gameplay.mode)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    pub fn randomizer(&self) -> RandomizerType {
+        self.gameplay
+            .randomizer
+            .get(&self.gameplay.mode)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    pub fn board(&self) -> Board {
+        self.gameplay
+            .board
+            .get(&self.gameplay.mode)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    pub fn mode(&self) -> Box<dyn GameMode> {
+        let settings = ModeSettings {
+            start_level: self.gameplay.start_level,
+            gravity: self.gameplay.gravity.clone(),
+            time_limit: u64::from(self.gameplay.ultra_time) * 1000,
+            common: CommonSettings {
+                scoring: scoring::find(self.scoring()),
+                rotation_system: self.rotation_system(),
+                randomizer: self.randomizer(),
+                board: self.board(),
+            },
+            ..ModeSettings::default()
+        };
+
+        // Unknown modes in the config file fall back to the first one
+        mode::create(&self.gameplay.mode, &settings)
+            .or_else(|| mode::create(mode::MODES[0], &settings))
+            .unwrap()
+    }
+
+    /// Mode selection in the menu bar, returns true if another mode was chosen
+    pub fn draw_modes(&mut self, state: &SettingsState, ui: &Ui) -> bool {
+        let mut chosen = false;
+
+        if let Some(menu) = ui.begin_menu(im_str!("Mode"), true) {
+            for &name in mode::MODES.iter() {
+                let selected = self.gameplay.mode == name;
+
+                if imgui::MenuItem::new(&ImString::new(name))
+                    .selected(selected)
+                    .build(ui)
+                    && !selected
+                {
+                    self.gameplay.mode = String::from(name);
+                    chosen = true;
+                }
+            }
+
+            ui.separator();
+
+            if let Some(scoring_menu) = ui.begin_menu(im_str!("Scoring"), true) {
+                for name in &state.score_tables {
+                    let selected = self.scoring() == name;
+
+                    if imgui::MenuItem::new(&ImString::new(name.as_str()))
+                        .selected(selected)
+                        .build(ui)
+                        && !selected
+                    {
+                        let mode = self.gameplay.mode.clone();
+                        self.gameplay.scoring.insert(mode, name.clone());
+                        chosen = true;
+                    }
+                }
+
+                scoring_menu.end(ui);
+            }
+
+            if let Some(rotation_menu) = ui.begin_menu(im_str!("Rotation"), true) {
+                for &rotation_system in ROTATION_SYSTEMS.iter() {
+                    let selected = self.rotation_system() == rotation_system;
+
+                    if imgui::MenuItem::new(&ImString::new(rotation_system.name()))
+                        .selected(selected)
+                        .build(ui)
+                        && !selected
+                    {
+                        let mode = self.gameplay.mode.clone();
+                        self.gameplay.rotation_system.insert(mode, rotation_system);
+                        chosen = true;
+                    }
+                }
+
+                rotation_menu.end(ui);
+            }
+
+            if let Some(randomizer_menu) = ui.begin_menu(im_str!("Randomizer"), true) {
+                for &randomizer in RANDOMIZERS.iter() {
+                    let selected = self.randomizer() == randomizer;
+
+                    if imgui::MenuItem::new(&ImString::new(randomizer.name()))
+                        .selected(selected)
+                        .build(ui)
+                        && !selected
+                    {
+                        let mode = self.gameplay.mode.clone();
+                        self.gameplay.randomizer.insert(mode, randomizer);
+                        chosen = true;
+                    }
+                }
+
+                randomizer_menu.end(ui);
+            }
+
+            if let Some(board_menu) = ui.begin_menu(im_str!("Board"), true) {
+                let mut board = self.board();
+                let mut changed = false;
+                // The game restarts only once a slider is let go
+                let mut edited = false;
+
+                changed |= Slider::new(im_str!("Width"), MIN_WIDTH..=MAX_WIDTH)
+                    .build(ui, &mut board.width);
+                edited |= ui.is_item_deactivated_after_edit();
+                changed |= Slider::new(im_str!("Height"), MIN_HEIGHT..=MAX_HEIGHT)
+                    .build(ui, &mut board.height);
+                edited |= ui.is_item_deactivated_after_edit();
+                changed |= Slider::new(im_str!("Buffer rows"), 0..=MAX_BUFFER)
+                    .build(ui, &mut board.buffer);
+                edited |= ui.is_item_deactivated_after_edit();
+                changed |= Slider::new(im_str!("Next pieces"), 0..=MAX_PREVIEW)
+                    .build(ui, &mut board.preview);
+                edited |= ui.is_item_deactivated_after_edit();
+                if ui.checkbox(im_str!("Hold"), &mut board.hold) {
+                    changed = true;
+                    edited = true;
+                }
+
+                // Typed in values can leave the range of the sliders
+                if changed {
+                    let mode = self.gameplay.mode.clone();
+                    self.gameplay.board.insert(mode, board.clamped());
+                }
+                chosen |= edited;
+
+                board_menu.end(ui);
+            }
+
+            menu.end(ui);
+        }
+
+        chosen
+    }
+
     pub fn tileset(&self, ctx: &mut Context, state: &SettingsState) -> GameResult<Image> {
         Image::new(
             ctx,
@@ -148,7 +435,7 @@ impl Settings {
 
             let id = ui.push_font(bold);
             ui.text_colored(header_color, im_str!("Graphics"));
-            id.pop(&ui);
+            id.pop(ui);
             ui.separator();
 
             {
@@ -169,13 +456,13 @@ impl Settings {
                 if ui.checkbox(im_str!(""), &mut self.graphics.vsync) {
                     restart_popup = true;
                 }
-                id.pop(&ui);
+                id.pop(ui);
 
                 ui.text(im_str!("Background"));
                 ui.same_line(pos);
                 let id = ui.push_id(im_str!("background"));
                 ui.checkbox(im_str!(""), &mut self.graphics.animated_background);
-                id.pop(&ui);
+                id.pop(ui);
 
                 ui.text(im_str!("Hide menu"));
                 ui.same_line(pos);
@@ -187,7 +474,7 @@ impl Settings {
                 ui.same_line(pos);
                 let id = ui.push_id(im_str!("sampling"));
                 if ComboBox::new(im_str!("")).build_simple_string(
-                    &ui,
+                    ui,
                     &mut sampling_id,
                     &[
                         im_str!("Off"),
@@ -201,7 +488,7 @@ impl Settings {
                     self.graphics.multi_sampling = SAMPLINGS[sampling_id];
                     restart_popup = true;
                 }
-                id.pop(&ui);
+                id.pop(ui);
 
                 if restart_popup {
                     ui.open_popup(im_str!("Restart needed"));
@@ -211,45 +498,91 @@ impl Settings {
             ui.separator();
             let id = ui.push_font(bold);
             ui.text_colored(header_color, im_str!("Gameplay"));
-            id.pop(&ui);
+            id.pop(ui);
             ui.separator();
 
             {
+                ui.text(im_str!("Player"));
+                ui.same_line(pos);
+                let id = ui.push_id(im_str!("player"));
+                if ui.input_text(im_str!(""), &mut state.player).build() {
+                    self.gameplay.player = String::from(state.player.to_str());
+                }
+                id.pop(ui);
+
+                ui.text(im_str!("Ultra time"));
+                ui.same_line(pos);
+                let id = ui.push_id(im_str!("ultra_time"));
+                Slider::new(im_str!(""), 30..=600)
+                    .display_format(im_str!("%d s"))
+                    .build(ui, &mut self.gameplay.ultra_time);
+                id.pop(ui);
+
                 ui.text(im_str!("Ghost piece"));
                 ui.same_line(pos);
                 let id = ui.push_id(im_str!("ghost_piece"));
-                Slider::new(im_str!(""), 0..=100).build(&ui, &mut self.gameplay.ghost_piece);
-                id.pop(&ui);
+                Slider::new(im_str!(""), 0..=100).build(ui, &mut self.gameplay.ghost_piece);
+                id.pop(ui);
+
+                ui.text(im_str!("Race overlay"));
+                ui.same_line(pos);
+                let id = ui.push_id(im_str!("ghost_overlay"));
+                ui.checkbox(im_str!(""), &mut self.gameplay.ghost_overlay);
+                id.pop(ui);
 
                 ui.text(im_str!("Block size"));
                 ui.same_line(pos);
                 let id = ui.push_id(im_str!("block_size"));
-                Slider::new(im_str!(""), 24..=43).build(&ui, &mut self.gameplay.block_size);
-                id.pop(&ui);
+                Slider::new(im_str!(""), 24..=43).build(ui, &mut self.gameplay.block_size);
+                id.pop(ui);
 
                 ui.text(im_str!("Entry delay"));
                 ui.same_line(pos);
                 let id = ui.push_id(im_str!("entry_delay"));
-                Slider::new(im_str!(""), 0..=500).build(&ui, &mut self.gameplay.entry_delay);
-                id.pop(&ui);
+                Slider::new(im_str!(""), 0..=500).build(ui, &mut self.gameplay.entry_delay);
+                id.pop(ui);
 
                 ui.text(im_str!("Lock delay"));
                 ui.same_line(pos);
                 let id = ui.push_id(im_str!("lock_delay"));
-                Slider::new(im_str!(""), 0..=1000).build(&ui, &mut self.gameplay.lock_delay);
-                id.pop(&ui);
+                Slider::new(im_str!(""), 0..=1000).build(ui, &mut self.gameplay.lock_delay);
+                id.pop(ui);
+
+                let mut lock_reset_id = LOCK_RESETS
+                    .iter()
+                    .position(|&l| l == self.gameplay.lock_reset)
+                    .unwrap_or_default();
+                let labels: Vec<ImString> = LOCK_RESETS
+                    .iter()
+                    .map(|l| ImString::new(l.name()))
+                    .collect();
+                let labels: Vec<&ImStr> = labels.iter().map(|l| l.as_ref()).collect();
+
+                ui.text(im_str!("Lock reset"));
+                ui.same_line(pos);
+                let id = ui.push_id(im_str!("lock_reset"));
+                if ComboBox::new(im_str!("")).build_simple_string(ui, &mut lock_reset_id, &labels) {
+                    self.gameplay.lock_reset = LOCK_RESETS[lock_reset_id];
+                }
+                id.pop(ui);
 
                 ui.text(im_str!("Clear delay"));
                 ui.same_line(pos);
                 let id = ui.push_id(im_str!("clear_delay"));
-                Slider::new(im_str!(""), 0..=500).build(&ui, &mut self.gameplay.clear_delay);
-                id.pop(&ui);
+                Slider::new(im_str!(""), 0..=500).build(ui, &mut self.gameplay.clear_delay);
+                id.pop(ui);
+
+                ui.text(im_str!("Start level"));
+                ui.same_line(pos);
+                let id = ui.push_id(im_str!("start_level"));
+                Slider::new(im_str!(""), 1..=20).build(ui, &mut self.gameplay.start_level);
+                id.pop(ui);
 
                 ui.text(im_str!("Skin"));
                 ui.same_line(pos);
                 let skins: Vec<&ImStr> = state.skins_imstr.iter().map(|s| s.as_ref()).collect();
                 let id = ui.push_id(im_str!("skins"));
-                if ComboBox::new(im_str!("")).build_simple_string(&ui, &mut state.skin_id, &skins) {
+                if ComboBox::new(im_str!("")).build_simple_string(ui, &mut state.skin_id, &skins) {
                     state.skin_switched = true;
 
                     self.gameplay.skin = String::from(
@@ -260,59 +593,121 @@ impl Settings {
                             .to_str(),
                     );
                 }
-                id.pop(&ui);
+                id.pop(ui);
 
                 ui.text(im_str!("Stack grid"));
                 ui.same_line(pos);
                 let id = ui.push_id(im_str!("stack_grid"));
                 ui.checkbox(im_str!(""), &mut self.gameplay.stack_grid);
-                id.pop(&ui);
+                id.pop(ui);
 
                 ui.text(im_str!("Stack outline"));
                 ui.same_line(pos);
                 let id = ui.push_id(im_str!("stack_outline"));
                 ui.checkbox(im_str!(""), &mut self.gameplay.stack_outline);
-                id.pop(&ui);
+                id.pop(ui);
             }
 
             ui.separator();
             let id = ui.push_font(bold);
             ui.text_colored(header_color, im_str!("Audio"));
-            id.pop(&ui);
+            id.pop(ui);
             ui.separator();
 
             {
                 ui.text(im_str!("Music"));
                 ui.same_line(pos);
                 let id = ui.push_id(im_str!("music"));
-                Slider::new(im_str!(""), 0..=100).build(&ui, &mut self.audio.music_volume);
-                id.pop(&ui);
+                Slider::new(im_str!(""), 0..=100).build(ui, &mut self.audio.music_volume);
+                id.pop(ui);
 
                 ui.text(im_str!("SFX"));
                 ui.same_line(pos);
                 let id = ui.push_id(im_str!("sfx"));
-                Slider::new(im_str!(""), 0..=100).build(&ui, &mut self.audio.sfx_volume);
-                id.pop(&ui);
+                Slider::new(im_str!(""), 0..=100).build(ui, &mut self.audio.sfx_volume);
+                id.pop(ui);
             }
 
             ui.separator();
             let id = ui.push_font(bold);
             ui.text_colored(header_color, im_str!("Input"));
-            id.pop(&ui);
+            id.pop(ui);
             ui.separator();
 
             {
                 ui.text(im_str!("DAS"));
                 ui.same_line(pos);
                 let id = ui.push_id(im_str!("das"));
-                Slider::new(im_str!(""), 100..=500).build(&ui, &mut self.input.das);
-                id.pop(&ui);
+                Slider::new(im_str!(""), 100..=500).build(ui, &mut self.input.das);
+                id.pop(ui);
 
                 ui.text(im_str!("ARR"));
                 ui.same_line(pos);
                 let id = ui.push_id(im_str!("arr"));
-                Slider::new(im_str!(""), 5..=200).build(&ui, &mut self.input.arr);
-                id.pop(&ui);
+                Slider::new(im_str!(""), 5..=200).build(ui, &mut self.input.arr);
+                id.pop(ui);
+
+                ui.text(im_str!("Instant soft drop"));
+                ui.same_line(pos);
+                let id = ui.push_id(im_str!("instant_soft_drop"));
+                ui.checkbox(im_str!(""), &mut self.input.instant_soft_drop);
+                id.pop(ui);
+
+                if !self.input.instant_soft_drop {
+                    ui.text(im_str!("Soft drop factor"));
+                    ui.same_line(pos);
+                    let id = ui.push_id(im_str!("soft_drop_factor"));
+                    Slider::new(im_str!(""), 5..=40)
+                        .display_format(im_str!("%dx"))
+                        .build(ui, &mut self.input.soft_drop_factor);
+                    id.pop(ui);
+                }
+
+                ui.text(im_str!("IRS/IHS"));
+                ui.same_line(pos);
+                let id = ui.push_id(im_str!("initial_actions"));
+                ui.checkbox(im_str!(""), &mut self.input.initial_actions);
+                id.pop(ui);
+            }
+
+            ui.separator();
+            let id = ui.push_font(bold);
+            ui.text_colored(header_color, im_str!("Replays"));
+            id.pop(ui);
+            ui.separator();
+
+            {
+                let mut auto_save_id = AUTO_SAVES
+                    .iter()
+                    .position(|&(a, _)| a == self.replays.auto_save)
+                    .unwrap_or_default();
+                let labels: Vec<ImString> =
+                    AUTO_SAVES.iter().map(|&(_, l)| ImString::new(l)).collect();
+                let labels: Vec<&ImStr> = labels.iter().map(|l| l.as_ref()).collect();
+
+                ui.text(im_str!("Auto-save"));
+                ui.same_line(pos);
+                let id = ui.push_id(im_str!("auto_save"));
+                if ComboBox::new(im_str!("")).build_simple_string(ui, &mut auto_save_id, &labels) {
+                    self.replays.auto_save = AUTO_SAVES[auto_save_id].0;
+                }
+                id.pop(ui);
+
+                if self.replays.auto_save == AutoSave::Last {
+                    ui.text(im_str!("Keep last"));
+                    ui.same_line(pos);
+                    let id = ui.push_id(im_str!("keep_last"));
+                    Slider::new(im_str!(""), 1..=100).build(ui, &mut self.replays.keep_last);
+                    id.pop(ui);
+                }
+
+                ui.text(im_str!("Size cap"));
+                ui.same_line(pos);
+                let id = ui.push_id(im_str!("max_size"));
+                Slider::new(im_str!(""), 1..=1000)
+                    .display_format(im_str!("%d MB"))
+                    .build(ui, &mut self.replays.max_size);
+                id.pop(ui);
             }
 
             ui.popup_modal(im_str!("Restart needed")).build(|| {
@@ -334,3 +729,52 @@ impl Settings {
         }
     }
 }
+
+#[test]
+fn baseline_config_test() {
+    // Written by the first version of the config file
+    let config = r#"
+[graphics]
+window_size = [1024, 768]
+fullscreen = false
+vsync = true
+animated_background = false
+hide_menu = false
+multi_sampling = "Four"
+
+[gameplay]
+block_size = 32
+ghost_piece = 20
+entry_delay = 100
+lock_delay = 400
+clear_delay = 200
+skin = "nblox.png"
+stack_grid = false
+stack_outline = true
+
+[audio]
+music_volume = 10
+sfx_volume = 70
+
+[input]
+das = 150
+arr = 20
+"#;
+
+    let settings: Settings = toml::from_str(config).unwrap();
+    assert_eq!(settings.graphics.window_size, (1024, 768));
+    assert_eq!(settings.graphics.multi_sampling, NumSamples::Four);
+    assert_eq!(settings.gameplay.block_size, 32);
+    assert_eq!(settings.gameplay.lock_delay, 400);
+    assert!(!settings.gameplay.stack_grid);
+    assert_eq!(settings.audio.music_volume, 10);
+    assert_eq!((settings.input.das, settings.input.arr), (150, 20));
+    assert!(!settings.input.initial_actions);
+    assert_eq!(settings.input.soft_drop_factor, 20);
+
+    assert_eq!(settings.gameplay.mode, "Marathon");
+    assert_eq!(settings.gameplay.lock_reset, LockReset::Infinite);
+    assert_eq!(settings.gameplay.start_level, 1);
+    assert_eq!(settings.gameplay.gravity, Gravity::guideline().levels);
+    assert_eq!(settings.replays.auto_save, AutoSave::Off);
+}
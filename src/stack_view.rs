@@ -0,0 +1,360 @@
+use std::time::Duration;
+
+use ggez::{
+    graphics::{self, Color, DrawMode, DrawParam, Mesh, MeshBuilder, Rect},
+    nalgebra::{Point2, Vector2},
+    timer, Context, GameResult,
+};
+use klocki_engine::stack::{Grid, Row, Stack};
+use rand_distr::{Distribution, Normal, Uniform};
+
+use crate::{blocks::Blocks, global::Global, utils};
+
+struct DestroyedBlock {
+    block_id: usize,
+    position: Vector2<f32>,
+    speed: Vector2<f32>,
+    rotation: f32,
+    rotation_speed: f32,
+    visible: Duration,
+    lifetime: Duration,
+    alpha: f32,
+}
+
+struct Randomizer {
+    pub uniform_vx: Uniform<f32>,
+    pub normal_vy: Normal<f32>,
+    pub uniform_vr: Uniform<f32>,
+    pub uniform_lifetime: Uniform<u64>,
+}
+
+impl Randomizer {
+    fn new() -> Randomizer {
+        Randomizer {
+            uniform_vx: Uniform::new(-7.5, 7.5),
+            normal_vy: Normal::new(-10.0, 5.0).unwrap(),
+            uniform_vr: Uniform::new(-8.0 * std::f32::consts::PI, 8.0 * std::f32::consts::PI),
+            uniform_lifetime: Uniform::new(500, 1000),
+        }
+    }
+}
+
+/// Settings that the grid mesh was built with
+#[derive(PartialEq)]
+struct GridKey {
+    grid: Grid,
+    block_size: i32,
+    stack_grid: bool,
+    stack_outline: bool,
+}
+
+/// Draws the stack of the engine and animates the destroyed blocks
+pub struct StackView {
+    destroyed_blocks: Vec<DestroyedBlock>,
+    randomizer: Randomizer,
+    grid_mesh: Option<Mesh>,
+    grid_key: Option<GridKey>,
+}
+
+impl StackView {
+    pub fn new() -> StackView {
+        StackView {
+            destroyed_blocks: vec![],
+            randomizer: Randomizer::new(),
+            grid_mesh: None,
+            grid_key: None,
+        }
+    }
+
+    pub fn destroy(&mut self, rows: &[(i32, Row)], vanish: i32) {
+        let mut rng = rand::thread_rng();
+
+        for (y, row) in rows {
+            for (x, &block_id) in row.iter().enumerate() {
+                if block_id == 0 {
+                    continue;
+                }
+
+                let vx = self.randomizer.uniform_vx.sample(&mut rng);
+                let vy = self.randomizer.normal_vy.sample(&mut rng);
+                let vr = self.randomizer.uniform_vr.sample(&mut rng);
+                let lifetime =
+                    Duration::from_millis(self.randomizer.uniform_lifetime.sample(&mut rng));
+
+                self.destroyed_blocks.push(DestroyedBlock {
+                    block_id,
+                    position: Vector2::new(x as f32, (y - vanish) as f32),
+                    speed: Vector2::new(vx, vy),
+                    rotation: 0.0,
+                    rotation_speed: vr,
+                    visible: Duration::new(0, 0),
+                    lifetime,
+                    alpha: 1.0,
+                });
+            }
+        }
+    }
+
+    pub fn update(&mut self, ctx: &mut Context) {
+        let dt = utils::dt_f32(ctx);
+        let g_force = Vector2::new(0.0, 75.0) * dt;
+
+        for block in &mut self.destroyed_blocks {
+            block.speed += g_force;
+            block.position += block.speed * dt;
+            block.rotation += block.rotation_speed * dt;
+            block.visible += timer::delta(ctx);
+            block.alpha = (1.0
+                - timer::duration_to_f64(block.visible) / timer::duration_to_f64(block.lifetime))
+                as f32;
+        }
+
+        self.destroyed_blocks
+            .retain(|block| block.visible < block.lifetime);
+    }
+
+    fn build_grid(
+        &mut self,
+        ctx: &mut Context,
+        stack: &Stack,
+        block_size: i32,
+        grid: bool,
+        outline: bool,
+    ) -> GameResult {
+        let mut grid_mesh = MeshBuilder::new();
+
+        const GRID_COLOR: Color = Color::new(0.1, 0.11, 0.12, 0.5);
+        const OUTLINE_COLOR: Color = Color::new(0.7, 0.8, 0.9, 0.8);
+        const BACKGROUND_COLOR: Color = Color::new(0.02, 0.03, 0.04, 0.95);
+
+        const GRID_WIDTH: f32 = 1.0;
+        const OUTLINE_WIDTH: f32 = 3.0;
+
+        grid_mesh.rectangle(
+            DrawMode::fill(),
+            Rect::new(
+                0.0,
+                0.0,
+                (stack.width * block_size) as f32,
+                (stack.height * block_size) as f32,
+            ),
+            BACKGROUND_COLOR,
+        );
+
+        if grid {
+            for y in stack.vanish..stack.vanish + stack.height {
+                for x in 0..stack.width {
+                    if stack.grid()[y as usize][x as usize] != 0 {
+                        continue;
+                    }
+
+                    let y = y - stack.vanish;
+
+                    grid_mesh.rectangle(
+                        DrawMode::stroke(GRID_WIDTH),
+                        Rect::new(
+                            (x * block_size) as f32,
+                            (y * block_size) as f32,
+                            block_size as f32,
+                            block_size as f32,
+                        ),
+                        GRID_COLOR,
+                    );
+                }
+            }
+        }
+
+        if outline {
+            for y in stack.vanish..stack.vanish + stack.height {
+                for x in 0..stack.width {
+                    if stack.grid()[y as usize][x as usize] == 0 {
+                        continue;
+                    }
+
+                    let mut up = false;
+                    let mut down = false;
+                    let mut left = false;
+                    let mut right = false;
+
+                    if y == stack.vanish || stack.grid()[y as usize - 1][x as usize] == 0 {
+                        up = true;
+                    }
+
+                    if y == stack.vanish + stack.height - 1
+                        || stack.grid()[y as usize + 1][x as usize] == 0
+                    {
+                        down = true;
+                    }
+
+                    if x == 0 || stack.grid()[y as usize][x as usize - 1] == 0 {
+                        left = true;
+                    }
+
+                    if x == stack.width - 1 || stack.grid()[y as usize][x as usize + 1] == 0 {
+                        right = true;
+                    }
+
+                    let y = y - stack.vanish;
+
+                    let corner = 1.0;
+
+                    if up {
+                        grid_mesh.line(
+                            &[
+                                Point2::new(
+                                    (x * block_size) as f32 - corner,
+                                    (y * block_size) as f32,
+                                ),
+                                Point2::new(
+                                    ((x + 1) * block_size) as f32 + corner,
+                                    (y * block_size) as f32,
+                                ),
+                            ],
+                            OUTLINE_WIDTH,
+                            OUTLINE_COLOR,
+                        )?;
+                    }
+
+                    if left {
+                        grid_mesh.line(
+                            &[
+                                Point2::new(
+                                    (x * block_size) as f32,
+                                    (y * block_size) as f32 - corner,
+                                ),
+                                Point2::new(
+                                    (x * block_size) as f32,
+                                    ((y + 1) * block_size) as f32 + corner,
+                                ),
+                            ],
+                            OUTLINE_WIDTH,
+                            OUTLINE_COLOR,
+                        )?;
+                    }
+
+                    if down {
+                        grid_mesh.line(
+                            &[
+                                Point2::new(
+                                    (x * block_size) as f32 - corner,
+                                    ((y + 1) * block_size) as f32,
+                                ),
+                                Point2::new(
+                                    ((x + 1) * block_size) as f32 + corner,
+                                    ((y + 1) * block_size) as f32,
+                                ),
+                            ],
+                            OUTLINE_WIDTH,
+                            OUTLINE_COLOR,
+                        )?;
+                    }
+
+                    if right {
+                        grid_mesh.line(
+                            &[
+                                Point2::new(
+                                    ((x + 1) * block_size) as f32,
+                                    (y * block_size) as f32 - corner,
+                                ),
+                                Point2::new(
+                                    ((x + 1) * block_size) as f32,
+                                    ((y + 1) * block_size) as f32 + corner,
+                                ),
+                            ],
+                            OUTLINE_WIDTH,
+                            OUTLINE_COLOR,
+                        )?;
+                    }
+                }
+            }
+        }
+
+        grid_mesh.rectangle(
+            DrawMode::stroke(3.0),
+            Rect::new(
+                0.0,
+                0.0,
+                (stack.width * block_size) as f32,
+                (stack.height * block_size) as f32,
+            ),
+            Color::new(0.8, 0.9, 1.0, 0.8),
+        );
+
+        self.grid_mesh = Some(grid_mesh.build(ctx)?);
+
+        Ok(())
+    }
+
+    pub fn draw(
+        &mut self,
+        ctx: &mut Context,
+        g: &Global,
+        stack: &Stack,
+        buffer: i32,
+        position: Point2<f32>,
+        blocks: &mut Blocks,
+    ) -> GameResult {
+        let block_size = g.settings.gameplay.block_size;
+
+        let key = GridKey {
+            grid: stack.grid().clone(),
+            block_size,
+            stack_grid: g.settings.gameplay.stack_grid,
+            stack_outline: g.settings.gameplay.stack_outline,
+        };
+
+        if self.grid_key.as_ref() != Some(&key) {
+            self.build_grid(ctx, stack, block_size, key.stack_grid, key.stack_outline)?;
+            self.grid_key = Some(key);
+        }
+
+        blocks.clear();
+
+        let alpha = 0.5;
+
+        // Rows of the buffer above the stack are drawn as well
+        for y in -buffer.min(stack.vanish)..stack.height {
+            let mut alpha = alpha;
+
+            if let Some((rows, ratio)) = stack.clearing() {
+                if rows.contains(&(stack.vanish + y)) {
+                    alpha *= 1.0 - ratio;
+                }
+            }
+
+            for x in 0..stack.width {
+                let block = stack.grid()[(stack.vanish + y) as usize][x as usize];
+                if block == 0 {
+                    continue;
+                }
+
+                let destination = Point2::new(
+                    position[0] + (x * block_size) as f32,
+                    position[1] + (y * block_size) as f32,
+                );
+
+                blocks.add(block, block_size, destination, alpha);
+            }
+        }
+
+        if let Some(grid_mesh) = &self.grid_mesh {
+            graphics::draw(ctx, grid_mesh, DrawParam::new().dest(position))?;
+        }
+
+        for block in &self.destroyed_blocks {
+            blocks.add_destroyed(
+                block.block_id,
+                block_size,
+                DrawParam::new()
+                    .dest(position + block.position * block_size as f32)
+                    .rotation(block.rotation)
+                    .offset(Point2::new(0.5, 0.5))
+                    .color(Color::new(1.0, 1.0, 1.0, 0.5 * block.alpha)),
+            );
+        }
+
+        blocks.draw(ctx)?;
+
+        Ok(())
+    }
+}
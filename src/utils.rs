@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use ggez::{
     filesystem,
     graphics::{self, Rect},
@@ -28,3 +30,15 @@ pub fn path(ctx: &Context, path: &str) -> String {
         String::from(path)
     }
 }
+
+/// Formats the time as `m:ss.mmm`
+pub fn format_time(time: Duration) -> String {
+    let millis = time.as_millis();
+
+    format!(
+        "{}:{:02}.{:03}",
+        millis / 60_000,
+        millis / 1000 % 60,
+        millis % 1000
+    )
+}
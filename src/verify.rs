@@ -0,0 +1,90 @@
+use std::{path::Path, time::Duration};
+
+use klocki_engine::{replay::ReplayData, End, Engine};
+
+use crate::utils;
+
+/// Simulates the replays without opening a window and prints their results.
+/// Returns the exit code, 1 if a result differs from the one in the header or can't be verified
+pub fn run(paths: Vec<String>) -> i32 {
+    if paths.is_empty() {
+        eprintln!("Usage: klocki verify <replay>...");
+        return 2;
+    }
+
+    let mut code = 0;
+
+    for path in &paths {
+        let replay_data = match ReplayData::load(Path::new(path)) {
+            Ok(replay_data) => replay_data,
+            Err(e) => {
+                eprintln!("{}: {}", path, e);
+                code = 2;
+                continue;
+            }
+        };
+
+        let mut engine = Engine::from_replay(&replay_data);
+        engine.finish_replay(replay_data.length());
+
+        let header = &replay_data.header;
+        let stats = engine.stats();
+
+        println!("{}", path);
+        println!("  Mode:       {}", header.mode);
+        println!("  Scoring:    {}", header.settings.mode.common.scoring.name);
+        println!(
+            "  Rotation:   {}",
+            header.settings.mode.common.rotation_system.name()
+        );
+        println!(
+            "  Randomizer: {}",
+            header.settings.mode.common.randomizer.name()
+        );
+        println!("  Lock reset: {}", header.settings.lock_reset.name());
+        let board = &header.settings.mode.common.board;
+        println!("  Board:      {}x{}", board.width, board.height);
+        println!("  Player:     {}", header.player);
+        println!("  Score:      {}", engine.score().score());
+        println!("  Lines:      {}", engine.lines());
+        println!("  Pieces:     {}", engine.pieces());
+        println!("  Time:       {}", utils::format_time(engine.time()));
+        println!("  PPS:        {:.2}", engine.pps());
+        println!("  T-Spins:    {}", stats.t_spins);
+        println!("  Mini:       {}", stats.t_spin_minis);
+        println!("  Tetrises:   {}", stats.tetrises);
+        println!("  Max combo:  {}", stats.max_combo);
+        println!("  Perfect:    {}", stats.perfect_clears);
+        println!(
+            "  Topped out: {}",
+            if engine.end() == Some(End::TopOut) {
+                "yes"
+            } else {
+                "no"
+            }
+        );
+
+        let time = engine.time().as_millis() as u64;
+        if replay_data.version == 0 {
+            println!("  MISMATCH: legacy replays don't have any results to compare with");
+            code = code.max(1);
+        } else if header.score != engine.score().score()
+            || header.lines != engine.lines()
+            || header.pieces != engine.pieces()
+            || header.time != time
+        {
+            println!(
+                "  MISMATCH: replay claims score {}, {} lines and {} pieces in {}",
+                header.score,
+                header.lines,
+                header.pieces,
+                utils::format_time(Duration::from_millis(header.time))
+            );
+            code = code.max(1);
+        } else {
+            println!("  Verified");
+        }
+    }
+
+    code
+}